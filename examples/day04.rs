@@ -1,95 +1,122 @@
-use aoc2025::get_input;
+use std::{io, path::Path, time::Instant};
+
+use aoc2025::{
+    automaton::peel,
+    bitgrid::BitGrid,
+    get_input,
+    grid::{Grid, NEIGHBORS8},
+    render::FrameWriter,
+};
 
 fn main() {
     let input = get_input(4).unwrap();
     let (part1, part2) = solve(&input);
     println!("{part1}");
     println!("{part2}");
+
+    // Set AOC_FRAMES to a directory to get one image per generation.
+    if let Ok(dir) = std::env::var("AOC_FRAMES") {
+        write_frames(&input, dir.as_ref()).unwrap();
+    }
+
+    // Set AOC_BENCH to a number of runs to time the byte grid against the bit-packed one, both
+    // a whole generation at a time, with the worklist `peel` for reference.
+    if let Ok(runs) = std::env::var("AOC_BENCH") {
+        let runs: u32 = runs.parse().unwrap();
+        for (name, solver) in [
+            ("grid", solve_grid as fn(&str) -> (usize, usize)),
+            ("bitgrid", solve_bitgrid),
+            ("peel", solve),
+        ] {
+            let start = Instant::now();
+            for _ in 0..runs {
+                assert_eq!(solver(&input), (part1, part2));
+            }
+            eprintln!("{name}: {:?} per run", start.elapsed() / runs);
+        }
+    }
 }
 
 fn solve(input: &str) -> (usize, usize) {
-    let mut grid: Vec<Vec<u8>> = input
-        .as_bytes()
-        .split(|x| *x == b'\n')
-        .map(|row| Vec::from(row))
-        .collect();
-    grid.pop(); // remove last empty line
+    let grid = Grid::parse(input, |b| b);
+    let peeling = peel(&grid, &NEIGHBORS8, 4, |&c| c == b'@');
 
-    let mut initial_state = true;
-    let mut initial_can_be_removed = 0;
+    (peeling.first_wave, peeling.total_removed)
+}
+
+fn write_frames(input: &str, dir: &Path) -> io::Result<()> {
+    let grid = Grid::parse(input, |b| b);
+    let peeling = peel(&grid, &NEIGHBORS8, 4, |&c| c == b'@');
+    let mut frames = FrameWriter::new(dir, "day04", 4)?;
+
+    // `None` for empty cells, then the generation in which the roll gets removed, if ever.
+    let rolls = Grid::from_fn(grid.width(), grid.height(), |x, y| {
+        (grid[(x, y)] == b'@').then_some(peeling.removed_at[(x, y)])
+    });
+
+    // Frame 0 is the input, frame `g` highlights the rolls removed in generation `g`.
+    for generation in 0..=peeling.generations {
+        frames.write(&rolls, |roll| match *roll {
+            None => [20, 20, 30],
+            Some(Some(g)) if g == generation => [255, 80, 40],
+            Some(Some(g)) if g < generation => [90, 40, 30],
+            Some(_) => [230, 230, 230],
+        })?;
+    }
 
-    let w = grid[0].len();
-    let h = grid.len();
+    Ok(())
+}
 
+/// Same answers as `solve`, one whole generation at a time on a byte grid.
+fn solve_grid(input: &str) -> (usize, usize) {
+    let mut rolls = Grid::parse(input, |b| b == b'@');
+    let mut initial_can_be_removed = None;
     let mut total_removed = 0;
+
     loop {
-        let has_roll =
-            |x: usize, y: usize| -> Option<bool> { grid.get(y)?.get(x).map(|l| *l == b'@') };
-
-        let neighbour_roll_count = |x: usize, y: usize| -> usize {
-            let mut count = 0;
-
-            for dy in -1..=1 {
-                for dx in -1..=1 {
-                    if dy == 0 && dx == 0 {
-                        continue;
-                    }
-
-                    let neighbour_has_roll = (|| {
-                        let x = x.checked_add_signed(dx)?;
-                        let y = y.checked_add_signed(dy)?;
-                        has_roll(x, y)
-                    })();
-
-                    if let Some(true) = neighbour_has_roll {
-                        count += 1;
-                    }
-                }
-            }
+        let counts = rolls.neighbor_counts(&NEIGHBORS8, |&roll| roll);
+        let removed = Grid::from_fn(rolls.width(), rolls.height(), |x, y| {
+            rolls[(x, y)] && counts[(x, y)] < 4
+        });
+        let count_removed = removed.iter().filter(|&(_, &r)| r).count();
 
-            count
-        };
-
-        let mut new_grid = Vec::with_capacity(h);
-        let mut count_removed = 0;
-        for y in 0..h {
-            let mut new_row = Vec::with_capacity(w);
-            for x in 0..w {
-                if has_roll(x, y).unwrap() {
-                    if neighbour_roll_count(x, y) < 4 {
-                        new_row.push(b'x');
-                        count_removed += 1;
-                    } else {
-                        new_row.push(b'@');
-                    }
-                } else {
-                    new_row.push(b'.');
-                }
-            }
+        initial_can_be_removed.get_or_insert(count_removed);
+        total_removed += count_removed;
 
-            new_grid.push(new_row);
+        if count_removed == 0 {
+            return (initial_can_be_removed.unwrap(), total_removed);
         }
 
-        if initial_state {
-            initial_can_be_removed = count_removed;
-            initial_state = false;
-        }
+        rolls = Grid::from_fn(rolls.width(), rolls.height(), |x, y| {
+            rolls[(x, y)] && !removed[(x, y)]
+        });
+    }
+}
 
-        total_removed += count_removed;
+/// Same answers as `solve_grid`, on a bit-packed grid.
+fn solve_bitgrid(input: &str) -> (usize, usize) {
+    let mut rolls = BitGrid::parse(input, |b| b == b'@');
+    let mut initial_can_be_removed = None;
+    let mut total_removed = 0;
+
+    loop {
+        let removed = rolls.and(&rolls.fewer_neighbors_than(&NEIGHBORS8, 4));
+        let count_removed = removed.count_ones();
 
-        std::mem::swap(&mut new_grid, &mut grid);
+        initial_can_be_removed.get_or_insert(count_removed);
+        total_removed += count_removed;
 
         if count_removed == 0 {
-            return (initial_can_be_removed, total_removed);
+            return (initial_can_be_removed.unwrap(), total_removed);
         }
+
+        rolls = rolls.and_not(&removed);
     }
 }
 
 #[test]
 fn test04() {
-    assert_eq!(
-        solve(
-            r"..@@.@@@@.
+    let example = r"..@@.@@@@.
 @@@.@.@.@@
 @@@@@.@.@@
 @.@@@@..@.
@@ -99,8 +126,9 @@ fn test04() {
 @.@@@.@@@@
 .@@@@@@@@.
 @.@.@@@.@.
-"
-        ),
-        (13, 43)
-    );
+";
+
+    assert_eq!(solve(example), (13, 43));
+    assert_eq!(solve_grid(example), (13, 43));
+    assert_eq!(solve_bitgrid(example), (13, 43));
 }
@@ -1,4 +1,4 @@
-use aoc2025::get_input;
+use aoc2025::{Grid, get_input};
 
 fn main() {
     let input = get_input(6).unwrap();
@@ -35,67 +35,46 @@ fn part1(input: &str) -> usize {
 }
 
 fn part2(input: &str) -> usize {
-    let input = input.as_bytes();
-    let mut accum: Vec<Option<usize>> = Vec::new();
+    // Numbers are written top to bottom, so each row of the transposed worksheet is a number,
+    // with the operator of its problem in the last position of the first one.
+    let worksheet = Grid::parse_padded(input, b' ', |b| b).transpose();
 
-    for line in input.split(|b| *b == b'\n') {
-        if matches!(line[0], b'*' | b'+') {
-            let mut out = 0;
-
-            let mut cur = None;
-            let mut intermediate = 0;
+    let mut out = 0;
+    let mut op = b'+';
+    let mut intermediate = 0;
 
-            for (e, a) in line.iter().zip(accum.into_iter()) {
-                match *e {
-                    b'*' => {
-                        cur = Some(b'*');
-                        out += intermediate;
-                        intermediate = 1;
-                    }
-                    b'+' => {
-                        cur = Some(b'+');
-                        out += intermediate;
-                        intermediate = 0;
-                    }
-                    _ => {}
-                }
+    for column in worksheet.rows() {
+        let (digits, operator) = column.split_at(column.len() - 1);
 
-                if let Some(a) = a {
-                    if let Some(b'*') = cur {
-                        intermediate *= a;
-                    } else if let Some(b'+') = cur {
-                        intermediate += a;
-                    } else {
-                        unreachable!()
-                    }
-                }
+        match operator[0] {
+            b'*' => {
+                out += intermediate;
+                op = b'*';
+                intermediate = 1;
             }
-
-            out += intermediate;
-
-            return out;
-        }
-
-        for (i, elem) in line.iter().enumerate() {
-            if *elem == b' ' {
-                accum.push(None);
-                continue;
+            b'+' => {
+                out += intermediate;
+                op = b'+';
+                intermediate = 0;
             }
+            _ => {}
+        }
 
-            let digit = (elem - b'0') as usize;
+        let digits = std::str::from_utf8(digits).unwrap().trim();
+        if digits.is_empty() {
+            // blank column between two problems
+            continue;
+        }
 
-            if let Some(Some(x)) = accum.get_mut(i) {
-                *x *= 10;
-                *x += digit;
-            } else if let Some(x) = accum.get_mut(i) {
-                *x = Some(digit);
-            } else {
-                accum.push(Some(digit));
-            }
+        let x: usize = digits.parse().unwrap();
+        if op == b'*' {
+            intermediate *= x;
+        } else {
+            intermediate += x;
         }
     }
 
-    unreachable!()
+    out + intermediate
 }
 
 #[test]
@@ -1,59 +1,91 @@
-use std::collections::{HashMap, HashSet};
+use std::{collections::HashMap, io, path::Path};
 
-use aoc2025::get_input;
+use aoc2025::{
+    Grid, get_input,
+    render::{FrameWriter, heat},
+};
 
 fn main() {
     let input = get_input(7).unwrap();
     let (part1, part2) = solve(&input);
     println!("{part1}");
     println!("{part2}");
+
+    // Set AOC_FRAMES to a directory to get an image of how many timelines go through each cell.
+    if let Ok(dir) = std::env::var("AOC_FRAMES") {
+        write_heat(&input, dir.as_ref()).unwrap();
+    }
 }
 
 fn solve(input: &str) -> (usize, usize) {
-    let mut splitters = HashSet::<(usize, usize)>::new();
-    let mut start = None;
-    let mut depth = 0;
-
-    for (y, line) in input.lines().enumerate() {
-        depth += 1;
-        for (x, c) in line.as_bytes().iter().enumerate() {
-            match c {
-                b'.' => {}
-                b'^' => {
-                    splitters.insert((x, y));
-                }
-                b'S' => {
-                    start = Some((x, y));
-                }
-                _ => panic!("Unexpected!"),
-            }
-        }
-    }
+    let (split_count, timelines, _) = trace(input);
+    (split_count, timelines)
+}
+
+/// Number of splits, number of timelines, and the number of beams going through every cell.
+fn trace(input: &str) -> (usize, usize, Grid<usize>) {
+    let grid = Grid::parse(input, |c| {
+        assert!(matches!(c, b'.' | b'^' | b'S'), "Unexpected!");
+        c
+    });
 
-    let (x, y) = start.unwrap();
+    let (x, y) = grid.positions().find(|&pos| grid[pos] == b'S').unwrap();
     let mut tachyons = HashMap::<usize, usize>::new();
     tachyons.insert(x, 1);
+    let mut beams = Grid::new(grid.width(), grid.height(), 0);
+    beams[(x, y)] = 1;
 
     let mut split_count = 0;
+    let mut escaped = 0;
 
-    for d in (y + 1)..=depth {
+    for d in (y + 1)..grid.height() {
         let mut new_tachyons = HashMap::<usize, usize>::with_capacity(tachyons.len());
 
         for (x, count) in tachyons.into_iter() {
-            if splitters.contains(&(x, d)) {
+            if grid[(x, d)] == b'^' {
                 split_count += 1;
 
-                *new_tachyons.entry(x - 1).or_default() += count;
-                *new_tachyons.entry(x + 1).or_default() += count;
+                // A beam split past either edge leaves the grid for good, but is still a timeline.
+                for x in [x.checked_sub(1), x.checked_add(1)] {
+                    match x.filter(|&x| x < grid.width()) {
+                        Some(x) => *new_tachyons.entry(x).or_default() += count,
+                        None => escaped += count,
+                    }
+                }
             } else {
                 *new_tachyons.entry(x).or_default() += count;
             }
         }
 
+        for (&x, &count) in &new_tachyons {
+            beams[(x, d)] = count;
+        }
         tachyons = new_tachyons;
     }
 
-    (split_count, tachyons.values().sum())
+    (
+        split_count,
+        tachyons.values().sum::<usize>() + escaped,
+        beams,
+    )
+}
+
+fn write_heat(input: &str, dir: &Path) -> io::Result<()> {
+    let grid = Grid::parse(input, |b| b);
+    let (_, _, beams) = trace(input);
+    let mut frames = FrameWriter::new(dir, "day07", 4)?;
+
+    // Counts grow exponentially down the grid, so the gradient follows their logarithm.
+    let max = beams.iter().map(|(_, &count)| count).max().unwrap_or(0);
+    let cells = Grid::from_fn(grid.width(), grid.height(), |x, y| {
+        (grid[(x, y)], beams[(x, y)])
+    });
+    frames.write(&cells, |&(cell, count)| match cell {
+        b'^' => [80, 160, 255],
+        _ => heat((count + 1).ilog2() as usize, (max + 1).ilog2() as usize),
+    })?;
+
+    Ok(())
 }
 
 #[test]
@@ -77,4 +109,14 @@ fn test07() {
 ";
 
     assert_eq!(solve(example), (21, 40));
+
+    let (_, timelines, beams) = trace(example);
+    let last_row: usize = (0..beams.width())
+        .map(|x| beams[(x, beams.height() - 1)])
+        .sum();
+    assert_eq!(last_row, timelines);
+
+    // A splitter in the first or last column sends one beam off the grid.
+    assert_eq!(solve(".S\n.^\n..\n"), (1, 2));
+    assert_eq!(solve("S.\n^.\n..\n"), (1, 2));
 }
@@ -1,105 +1,94 @@
-use std::{cmp::Reverse, collections::HashMap};
-
-use aoc2025::get_input;
+use aoc2025::{
+    dendrogram::Dendrogram,
+    get_input,
+    incremental::IncrementalClusters,
+    kdtree::pairs_by_distance,
+    mst::Kruskal,
+    point::{Metric, Point},
+};
 
 fn main() {
     let input = get_input(8).unwrap();
-    let (part1, part2) = solve(&input, 1000);
+    let (part1, part2) = solve(&input, 1000, Metric::Euclidean);
     println!("{part1}");
     println!("{part2}");
-}
-
-fn dist_sqr([x0, y0, z0]: &[usize; 3], [x1, y1, z1]: &[usize; 3]) -> usize {
-    let dx = x0.abs_diff(*x1);
-    let dy = y0.abs_diff(*y1);
-    let dz = z0.abs_diff(*z1);
-    dx * dx + dy * dy + dz * dz
-}
-
-fn solve(input: &str, n: usize) -> (usize, usize) {
-    let points: Vec<[usize; 3]> = input
-        .lines()
-        .map(|line| {
-            let p = line
-                .split(',')
-                .map(|w| w.parse().unwrap())
-                .collect::<Vec<usize>>();
-            p.try_into().unwrap()
-        })
-        .collect();
 
-    let mut dists = Vec::new();
-    for (i, a) in points.iter().enumerate() {
-        for b in points.iter().skip(i + 1) {
-            dists.push((a, b, dist_sqr(a, b)));
-        }
-    }
-    dists.sort_unstable_by(|(_, _, a), (_, _, b)| a.cmp(b));
-
-    let mut groups = HashMap::<usize, Vec<&[usize; 3]>>::new();
-    let mut belongs_to_group = HashMap::<&[usize; 3], usize>::new();
-    let mut next_group_id = 0;
-
-    macro_rules! step {
-        ($a:ident, $b:ident) => {
-            match (
-                belongs_to_group.get($a).copied(),
-                belongs_to_group.get($b).copied(),
-            ) {
-                (None, None) => {
-                    groups.insert(next_group_id, vec![$a, $b]);
-                    belongs_to_group.insert($a, next_group_id);
-                    belongs_to_group.insert($b, next_group_id);
-                    next_group_id += 1;
-                }
-                (None, Some(id)) => {
-                    groups.get_mut(&id).unwrap().push($a);
-                    belongs_to_group.insert($a, id);
-                }
-                (Some(id), None) => {
-                    groups.get_mut(&id).unwrap().push($b);
-                    belongs_to_group.insert($b, id);
-                }
-                (Some(id_a), Some(id_b)) => {
-                    if id_a != id_b {
-                        // merge groups
-                        let mut bs = groups.remove(&id_b).unwrap();
-                        for b in bs.iter() {
-                            *belongs_to_group.get_mut(b).unwrap() = id_a;
-                        }
-                        groups.get_mut(&id_a).unwrap().append(&mut bs);
-                    }
-                }
-            }
+    // Set AOC_DENDROGRAM to a `.json` or `.nwk` file name to get the whole clustering.
+    if let Ok(path) = std::env::var("AOC_DENDROGRAM") {
+        let dendrogram = dendrogram(&parse(&input), Metric::Euclidean);
+        let text = if path.ends_with(".json") {
+            dendrogram.to_json()
+        } else {
+            dendrogram.to_newick()
         };
+        std::fs::write(path, text).unwrap();
     }
 
-    let mut it = dists.into_iter();
-    for (a, b, _) in (&mut it).take(n) {
-        step!(a, b);
+    // Set AOC_STREAM_DIST to a squared distance to plug the boxes in one at a time instead.
+    if let Ok(max_dist) = std::env::var("AOC_STREAM_DIST") {
+        let steps = solve_streaming(&input, max_dist.parse().unwrap(), Metric::Euclidean);
+        for (i, (circuits, largest)) in steps.iter().enumerate() {
+            println!("{}: {circuits} circuits, {largest}", i + 1);
+        }
     }
+}
 
-    let mut group_sizes: Vec<usize> = groups.values().map(|v| v.len()).collect();
-    group_sizes.sort_unstable_by(|a, b| Reverse(a).cmp(&Reverse(b)));
+fn parse(input: &str) -> Vec<Point<i64, 3>> {
+    input.lines().map(|line| line.parse().unwrap()).collect()
+}
 
-    let out1 = group_sizes[0] * group_sizes[1] * group_sizes[2];
-    let mut out2 = 0;
+/// Single-linkage clustering of the junction boxes, equally distant pairs being connected in
+/// order of their indices.
+fn dendrogram(points: &[Point<i64, 3>], metric: Metric) -> Dendrogram<u128> {
+    // Candidate edges come closest first straight from a k-d tree, so only the ones Kruskal
+    // actually looks at are ever computed.
+    let kruskal = Kruskal::from_sorted(points.len(), pairs_by_distance(points, metric));
+    Dendrogram::from_events(points.len(), kruskal)
+}
 
-    for (a, b, _) in it {
-        step!(a, b);
+fn solve(input: &str, n: usize, metric: Metric) -> (usize, usize) {
+    let points = parse(input);
+    let dendrogram = dendrogram(&points, metric);
 
-        if groups.len() == 1 && groups.values().next().unwrap().len() == points.len() {
-            out2 = a[0] * b[0];
-            break;
-        }
-    }
+    let out1 = dendrogram
+        .clusters_at(n)
+        .iter()
+        .take(3)
+        .map(|c| c.len())
+        .product();
+
+    let last = dendrogram.merges().last().unwrap().edge;
+    // Widened so that large coordinates can't silently overflow.
+    let out2 = (points[last.a][0] as i128 * points[last.b][0] as i128)
+        .try_into()
+        .expect("product of x coordinates doesn't fit a usize");
 
     (out1, out2)
 }
 
-#[test]
-fn test08() {
-    let example = r"162,817,812
+/// Plugs the boxes in one at a time, giving the number of circuits and the product of the three
+/// largest after every box.
+///
+/// Unlike part 1, which connects the first `n` shortest pairs, a box is connected to every box
+/// at most `max_dist` away, so the results only match for a threshold that happens to select
+/// exactly those pairs.
+fn solve_streaming(input: &str, max_dist: u128, metric: Metric) -> Vec<(usize, usize)> {
+    let mut circuits = IncrementalClusters::new(metric, max_dist);
+
+    parse(input)
+        .into_iter()
+        .map(|p| {
+            circuits.insert(p);
+            (
+                circuits.component_count(),
+                circuits.largest(3).iter().product(),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = r"162,817,812
 57,618,57
 906,360,560
 592,479,940
@@ -121,5 +110,95 @@ fn test08() {
 425,690,689
 ";
 
-    assert_eq!(solve(example, 10), (40, 25272));
+#[test]
+fn test08() {
+    assert_eq!(solve(EXAMPLE, 10, Metric::Euclidean), (40, 25272));
+
+    for metric in [Metric::Euclidean, Metric::Manhattan, Metric::Chebyshev] {
+        assert_eq!(
+            solve(EXAMPLE, 10, metric),
+            solve_all_pairs(EXAMPLE, 10, metric)
+        );
+    }
+}
+
+#[test]
+fn test08_streaming() {
+    let points = parse(EXAMPLE);
+    let pairs: Vec<u128> = pairs_by_distance(&points, Metric::Euclidean)
+        .map(|e| e.weight)
+        .collect();
+
+    // Connecting everything up to the 10th shortest distance is part 1 once every box is in,
+    // as long as the 11th is strictly longer.
+    assert!(pairs[9] < pairs[10]);
+    let stream = solve_streaming(EXAMPLE, pairs[9], Metric::Euclidean);
+    assert_eq!(stream.len(), points.len());
+    assert_eq!(
+        stream.last().unwrap().1,
+        solve(EXAMPLE, 10, Metric::Euclidean).0
+    );
+    assert!(stream.windows(2).all(|w| w[1].0 <= w[0].0 + 1));
+
+    // And up to the edge closing the last circuit, everything ends up connected.
+    let last = dendrogram(&points, Metric::Euclidean)
+        .merges()
+        .last()
+        .unwrap()
+        .edge;
+    let stream = solve_streaming(EXAMPLE, last.weight, Metric::Euclidean);
+    assert_eq!(stream.last().unwrap().0, 1);
+}
+
+/// Sorts every pair by distance then indices, the plain way.
+#[cfg(test)]
+fn solve_all_pairs(input: &str, n: usize, metric: Metric) -> (usize, usize) {
+    use aoc2025::disjoint_set::DisjointSet;
+
+    let points = parse(input);
+    let mut pairs = Vec::new();
+    for i in 0..points.len() {
+        for j in i + 1..points.len() {
+            pairs.push((metric.distance(points[i], points[j]), i, j));
+        }
+    }
+    pairs.sort_unstable();
+
+    let mut circuits = DisjointSet::new(points.len());
+    let mut out1 = 0;
+    for (k, &(_, i, j)) in pairs.iter().enumerate() {
+        if k == n {
+            out1 = circuits.component_sizes().iter().take(3).product();
+        }
+        if circuits.union(i, j) && circuits.component_count() == 1 {
+            return (out1, (points[i][0] * points[j][0]) as usize);
+        }
+    }
+    unreachable!()
+}
+
+#[test]
+fn test08_ties() {
+    // Boxes on a small lattice, where most pairs are as far apart as many others and the tie
+    // break decides which circuits form and which pair closes the last one.
+    let input: String = (0..60)
+        .map(|i| {
+            format!(
+                "{},{},{}\n",
+                (i * 7) % 5 + 1,
+                (i * 3) % 4 + 1,
+                (i * 11) % 6 + 1
+            )
+        })
+        .collect();
+
+    for metric in [Metric::Euclidean, Metric::Manhattan, Metric::Chebyshev] {
+        for n in [5, 20, 40] {
+            let answer = solve(&input, n, metric);
+            assert_eq!(answer, solve_all_pairs(&input, n, metric));
+
+            // Nothing depends on hashing or unstable sorts, so a second run agrees.
+            assert_eq!(solve(&input, n, metric), answer);
+        }
+    }
 }
@@ -1,113 +1,77 @@
-use std::collections::BTreeMap;
+use std::{fs::File, io, io::BufWriter, path::Path};
 
-use aoc2025::get_input;
+use aoc2025::{
+    get_input,
+    point::Point,
+    polygon::{Rect, RectilinearPolygon},
+    svg::{Style, Svg},
+};
 
 fn main() {
     let input = get_input(9).unwrap();
     let (part1, part2) = solve(&input);
     println!("{part1}");
     println!("{part2}");
+
+    // Set AOC_SVG to a file name to see the loop and both rectangles.
+    if let Ok(path) = std::env::var("AOC_SVG") {
+        write_svg(&input, path.as_ref()).unwrap();
+    }
 }
 
-fn solve(input: &str) -> (usize, usize) {
-    let points: Vec<[usize; 2]> = input
+fn parse(input: &str) -> Vec<[i64; 2]> {
+    input
         .lines()
-        .map(|line| {
-            let p = line
-                .split(',')
-                .map(|w| w.parse().unwrap())
-                .collect::<Vec<usize>>();
-            p.try_into().unwrap()
-        })
-        .collect();
-
-    // compress points to make PIP cheaper
-    let x_mapping: BTreeMap<usize, usize> = {
-        let mut x_sorted: Vec<usize> = points.iter().map(|[x, _]| *x).collect();
-        x_sorted.sort();
-        x_sorted.dedup();
-        x_sorted
-            .into_iter()
-            .enumerate()
-            .map(|(i, x)| (x, i))
-            .collect()
-    };
-    let y_mapping: BTreeMap<usize, usize> = {
-        let mut y_sorted: Vec<usize> = points.iter().map(|[_, y]| *y).collect();
-        y_sorted.sort();
-        y_sorted.dedup();
-        y_sorted
-            .into_iter()
-            .enumerate()
-            .map(|(i, y)| (y, i))
-            .collect()
-    };
+        .map(|line| line.parse::<Point<i64, 2>>().unwrap().0)
+        .collect()
+}
 
-    // both the example and my input finish with a vertical segment, so I'm ignoring the loopback segment
-    let mut horiz_segments: Vec<(usize, (usize, usize))> = points
-        .windows(2)
-        .filter_map(|w| match w {
-            &[[x0, y0], [x1, y1]] => {
-                if y0 == y1 {
-                    Some((y_mapping[&y0], (x_mapping[&x0], x_mapping[&x1])))
-                } else {
-                    None
-                }
+/// Largest rectangles with red tiles as corners, anywhere and inside the loop.
+fn rectangles(points: &[[i64; 2]]) -> (Rect, Option<Rect>) {
+    let mut best = Rect::from_corners(points[0], points[0]);
+    for (i, &a) in points.iter().enumerate() {
+        for &b in points.iter().skip(i + 1) {
+            let rect = Rect::from_corners(a, b);
+            if rect.tile_count() > best.tile_count() {
+                best = rect;
             }
-            _ => panic!("Unexpected"),
-        })
-        .collect();
-    horiz_segments.sort_unstable_by_key(|(y, _)| *y);
+        }
+    }
 
-    let mut max_area = usize::MIN;
-    let mut max_restricted_area = usize::MIN;
+    let polygon = RectilinearPolygon::new(points.to_vec()).unwrap();
+    (best, polygon.largest_rectangle_with_corners(points))
+}
 
-    for (i, a) in points.iter().enumerate() {
-        'rect_test: for b in points.iter().skip(i + 1) {
-            // part 1
-            let area = (a[0].abs_diff(b[0]) + 1) * (a[1].abs_diff(b[1]) + 1);
-            max_area = max_area.max(area);
+fn solve(input: &str) -> (usize, usize) {
+    let (best, restricted) = rectangles(&parse(input));
 
-            // part 2
-            if area <= max_restricted_area {
-                // skip expensive checks
-                continue;
-            }
+    (
+        best.tile_count() as usize,
+        restricted.map_or(0, |rect| rect.tile_count() as usize),
+    )
+}
 
-            // compressed bounds
-            let x_from = x_mapping[&a[0]].min(x_mapping[&b[0]]);
-            let x_to = x_mapping[&a[0]].max(x_mapping[&b[0]]);
-            let y_from = y_mapping[&a[1]].min(y_mapping[&b[1]]);
-            let y_to = y_mapping[&a[1]].max(y_mapping[&b[1]]);
+fn write_svg(input: &str, path: &Path) -> io::Result<()> {
+    let points = parse(input);
+    let (best, restricted) = rectangles(&points);
 
-            for x in x_from..=x_to {
-                for y in y_from..=y_to {
-                    if !point_inside_polygon(&horiz_segments, [x, y]) {
-                        continue 'rect_test;
-                    }
-                }
-            }
-            max_restricted_area = max_restricted_area.max(area);
-        }
+    let mut svg = Svg::new();
+    svg.polygon(&points, Style::new("#2e7d3240", "#2e7d32"));
+    svg.rect(best, Style::new("#1565c030", "#1565c0"));
+    if let Some(rect) = restricted {
+        svg.rect(rect, Style::new("#c6282860", "#c62828"));
     }
-
-    (max_area, max_restricted_area)
-}
-
-fn point_inside_polygon(horiz_segments: &[(usize, (usize, usize))], [x, y]: [usize; 2]) -> bool {
-    // Doing PIP by casting a ray downwards from point and looking at intersecting segments
-    // select the one with the min y
-    if let Some((seg_y, (x0, x1))) = horiz_segments
-        .iter()
-        .copied()
-        .filter(|&(seg_y, (x0, x1))| seg_y >= y && ((x0 <= x && x <= x1) || (x1 <= x && x <= x0)))
-        .min_by_key(|(seg_y, _)| *seg_y)
-    {
-        // example and input seem to be non self intersecting polygon that is described clockwise
-        return seg_y == y || (x1 <= x && x <= x0);
+    for &p in &points {
+        svg.point(p, Style::new("#c62828", "none"));
+    }
+    svg.label(best.min, "part 1", Style::new("#1565c0", "none"));
+    if let Some(rect) = restricted {
+        svg.label(rect.min, "part 2", Style::new("#c62828", "none"));
     }
 
-    false
+    let mut file = BufWriter::new(File::create(path)?);
+    svg.write(&mut file)?;
+    io::Write::flush(&mut file)
 }
 
 #[test]
@@ -123,4 +87,17 @@ fn test09() {
 ";
 
     assert_eq!(solve(example), (50, 24));
+
+    // Same loop counter-clockwise, closed by a horizontal segment.
+    let reversed = r"11,7
+11,1
+7,1
+7,3
+2,3
+2,5
+9,5
+9,7
+";
+
+    assert_eq!(solve(reversed), (50, 24));
 }
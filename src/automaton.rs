@@ -0,0 +1,464 @@
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use crate::grid::{Grid, NEIGHBORS8};
+
+/// Result of [`peel`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Peeling {
+    /// Number of cells removed in the first generation.
+    pub first_wave: usize,
+    pub total_removed: usize,
+    /// Number of generations that removed at least one cell.
+    pub generations: usize,
+    /// Generation (starting at 1) in which every cell got removed, `None` if it never was.
+    pub removed_at: Grid<Option<usize>>,
+}
+
+/// Repeatedly removes alive cells with fewer than `threshold` alive neighbours in `stencil`,
+/// until nothing changes.
+///
+/// Every generation removes its cells all at once, exactly like rebuilding the grid at every
+/// step would. But neighbour counts are kept up to date, so only the cells around a removal are
+/// looked at again, which makes this linear in the number of cells (times the stencil size).
+pub fn peel<T>(
+    grid: &Grid<T>,
+    stencil: &[(isize, isize)],
+    threshold: usize,
+    alive: impl Fn(&T) -> bool,
+) -> Peeling {
+    let alive = grid.map(alive);
+    let mut counts = alive.neighbor_counts(stencil, |&a| a);
+    let mut removed_at = Grid::new(grid.width(), grid.height(), None);
+
+    let mut wave: Vec<(usize, usize)> = alive
+        .iter()
+        .filter(|&(pos, &a)| a && (counts[pos] as usize) < threshold)
+        .map(|(pos, _)| pos)
+        .collect();
+    for &pos in wave.iter() {
+        removed_at[pos] = Some(1);
+    }
+
+    let first_wave = wave.len();
+    let mut total_removed = 0;
+    let mut generations = 0;
+
+    while !wave.is_empty() {
+        generations += 1;
+        total_removed += wave.len();
+
+        // The whole wave is already marked as removed, so none of its cells get queued again
+        // while their neighbours are updated.
+        let mut next_wave = Vec::new();
+        for pos in wave {
+            for &(dx, dy) in stencil {
+                // Cells that had `pos` in their stencil lose one neighbour.
+                let Some(seen_by) = grid.offset(pos, (-dx, -dy)) else {
+                    continue;
+                };
+
+                counts[seen_by] -= 1;
+                if alive[seen_by]
+                    && removed_at[seen_by].is_none()
+                    && (counts[seen_by] as usize) < threshold
+                {
+                    removed_at[seen_by] = Some(generations + 1);
+                    next_wave.push(seen_by);
+                }
+            }
+        }
+
+        wave = next_wave;
+    }
+
+    Peeling {
+        first_wave,
+        total_removed,
+        generations,
+        removed_at,
+    }
+}
+
+/// State of a cell in a [`Simulation`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Cell {
+    Dead,
+    Alive,
+    /// A cell that died, like day04's `x` marker. It doesn't count as an alive neighbour.
+    Removed,
+}
+
+/// What happens to an alive cell when it dies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Death {
+    /// It becomes `Dead` right away, and may be born again.
+    Dead,
+    /// It stays `Removed` for one generation, then becomes `Dead`.
+    Transient,
+    /// It stays `Removed` for good and can never be born again.
+    Absorbing,
+}
+
+/// A life-like rule: the number of alive cells in `neighborhood` decides the next state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    pub neighborhood: Vec<(isize, isize)>,
+    /// Alive neighbour counts at which a dead cell is born.
+    pub birth: Vec<usize>,
+    /// Alive neighbour counts at which an alive cell survives.
+    pub survival: Vec<usize>,
+    pub death: Death,
+}
+
+impl Rule {
+    /// A `B.../S...` rule over the 8 surrounding cells.
+    pub fn life_like(birth: &[usize], survival: &[usize]) -> Self {
+        Self {
+            neighborhood: NEIGHBORS8.to_vec(),
+            birth: birth.to_vec(),
+            survival: survival.to_vec(),
+            death: Death::Dead,
+        }
+    }
+
+    /// Conway's game of life, `B3/S23`.
+    pub fn life() -> Self {
+        Self::life_like(&[3], &[2, 3])
+    }
+
+    /// Alive cells with fewer than `threshold` alive neighbours are removed, nothing is born.
+    ///
+    /// With `NEIGHBORS8`, a threshold of 4 and `Death::Transient` this is exactly day04.
+    pub fn peeling(neighborhood: &[(isize, isize)], threshold: usize, death: Death) -> Self {
+        Self {
+            neighborhood: neighborhood.to_vec(),
+            birth: Vec::new(),
+            survival: (threshold..=neighborhood.len()).collect(),
+            death,
+        }
+    }
+
+    pub fn next(&self, cell: Cell, alive_neighbors: usize) -> Cell {
+        match cell {
+            Cell::Alive if self.survival.contains(&alive_neighbors) => Cell::Alive,
+            Cell::Alive => match self.death {
+                Death::Dead => Cell::Dead,
+                Death::Transient | Death::Absorbing => Cell::Removed,
+            },
+            Cell::Dead if self.birth.contains(&alive_neighbors) => Cell::Alive,
+            Cell::Dead => Cell::Dead,
+            Cell::Removed => match self.death {
+                Death::Absorbing => Cell::Removed,
+                Death::Dead | Death::Transient => Cell::Dead,
+            },
+        }
+    }
+}
+
+/// Changes made by one [`Simulation::step`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Step {
+    pub born: usize,
+    pub died: usize,
+    /// Cells that changed state without being born or dying, e.g. `Removed` becoming `Dead`.
+    pub decayed: usize,
+}
+
+impl Step {
+    pub fn changed(&self) -> usize {
+        self.born + self.died + self.decayed
+    }
+}
+
+/// How [`Simulation::run_until_stable`] stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// Nothing changes anymore from `generation` onwards.
+    FixedPoint { generation: usize },
+    /// The state at `start` repeats every `period` generations.
+    Cycle { start: usize, period: usize },
+    /// The step budget ran out first.
+    StepLimit,
+}
+
+/// Runs a [`Rule`] over a finite grid, everything outside of it being dead.
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    rule: Rule,
+    grid: Grid<Cell>,
+    generation: usize,
+}
+
+impl Simulation {
+    pub fn new(rule: Rule, grid: Grid<Cell>) -> Self {
+        Self {
+            rule,
+            grid,
+            generation: 0,
+        }
+    }
+
+    pub fn grid(&self) -> &Grid<Cell> {
+        &self.grid
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn step(&mut self) -> Step {
+        let counts = self
+            .grid
+            .neighbor_counts(&self.rule.neighborhood, |&c| c == Cell::Alive);
+        let mut step = Step::default();
+
+        self.grid = Grid::from_fn(self.grid.width(), self.grid.height(), |x, y| {
+            let cell = self.grid[(x, y)];
+            let next = self.rule.next(cell, counts[(x, y)] as usize);
+            match (cell, next) {
+                (a, b) if a == b => {}
+                (_, Cell::Alive) => step.born += 1,
+                (Cell::Alive, _) => step.died += 1,
+                _ => step.decayed += 1,
+            }
+            next
+        });
+        self.generation += 1;
+
+        step
+    }
+
+    /// Runs `steps` generations, returning every step's changes.
+    pub fn run(&mut self, steps: usize) -> Vec<Step> {
+        (0..steps).map(|_| self.step()).collect()
+    }
+
+    /// Runs until a state repeats, or `max_steps` generations went by.
+    ///
+    /// Only hashes of past states are kept, a fixed point being a cycle of period 1. When a hash
+    /// comes back, the earlier state is replayed from a snapshot of the starting one to make sure
+    /// it really is the same and not a collision.
+    pub fn run_until_stable(&mut self, max_steps: usize) -> Outcome {
+        let (snapshot, first) = (self.grid.clone(), self.generation);
+        let mut seen: HashMap<u64, Vec<usize>> = HashMap::new();
+        seen.entry(hash(&self.grid))
+            .or_default()
+            .push(self.generation);
+
+        for _ in 0..max_steps {
+            self.step();
+
+            let generations = seen.entry(hash(&self.grid)).or_default();
+            for &start in generations.iter() {
+                let mut replay = Simulation::new(self.rule.clone(), snapshot.clone());
+                replay.run(start - first);
+                if replay.grid == self.grid {
+                    return match self.generation - start {
+                        1 => Outcome::FixedPoint { generation: start },
+                        period => Outcome::Cycle { start, period },
+                    };
+                }
+            }
+            generations.push(self.generation);
+        }
+
+        Outcome::StepLimit
+    }
+}
+
+fn hash(grid: &Grid<Cell>) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    grid.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::{NEIGHBORS4, NEIGHBORS8};
+    use crate::rng::Rng;
+
+    /// Rebuilds the whole grid every generation.
+    fn naive_peel(
+        grid: &Grid<bool>,
+        stencil: &[(isize, isize)],
+        threshold: usize,
+    ) -> Grid<Option<usize>> {
+        let mut alive = grid.clone();
+        let mut removed_at = Grid::new(grid.width(), grid.height(), None);
+
+        for generation in 1.. {
+            let counts = alive.neighbor_counts(stencil, |&a| a);
+            let mut changed = false;
+            for pos in grid.positions() {
+                if alive[pos] && (counts[pos] as usize) < threshold {
+                    removed_at[pos] = Some(generation);
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+            alive = Grid::from_fn(grid.width(), grid.height(), |x, y| {
+                alive[(x, y)] && removed_at[(x, y)].is_none()
+            });
+        }
+
+        removed_at
+    }
+
+    #[test]
+    fn test_peel_generations() {
+        let grid = Grid::parse("@@@@\n@@@@\n@@@.\n", |b| b == b'@');
+        let peeling = peel(&grid, &NEIGHBORS8, 4, |&a| a);
+
+        // Corners go first, then the cells that only had them as spare neighbours.
+        assert_eq!(peeling.first_wave, 3);
+        assert_eq!(peeling.removed_at[(0, 0)], Some(1));
+        assert_eq!(peeling.removed_at[(3, 1)], Some(2));
+        assert_eq!(peeling.removed_at[(3, 2)], None);
+        assert_eq!(peeling.removed_at, naive_peel(&grid, &NEIGHBORS8, 4));
+    }
+
+    #[test]
+    fn test_peel_matches_naive() {
+        let mut rng = Rng::new(4);
+
+        for _ in 0..200 {
+            let width = rng.range(1..12);
+            let height = rng.range(1..12);
+            let grid = Grid::from_fn(width, height, |_, _| !rng.one_in(4));
+
+            for (stencil, threshold) in [(&NEIGHBORS8[..], 4), (&NEIGHBORS4[..], 3)] {
+                let peeling = peel(&grid, stencil, threshold, |&a| a);
+                let expected = naive_peel(&grid, stencil, threshold);
+
+                assert_eq!(peeling.removed_at, expected);
+                assert_eq!(
+                    peeling.total_removed,
+                    expected.iter().filter(|(_, g)| g.is_some()).count()
+                );
+                assert_eq!(
+                    peeling.first_wave,
+                    expected.iter().filter(|(_, g)| **g == Some(1)).count()
+                );
+            }
+        }
+    }
+
+    fn parse_cells(input: &str) -> Grid<Cell> {
+        Grid::parse(input, |b| match b {
+            b'@' | b'#' => Cell::Alive,
+            b'x' => Cell::Removed,
+            _ => Cell::Dead,
+        })
+    }
+
+    #[test]
+    fn test_life_fixed_point_and_cycle() {
+        let block = parse_cells("....\n.##.\n.##.\n....\n");
+        let mut sim = Simulation::new(Rule::life(), block.clone());
+        assert_eq!(
+            sim.run_until_stable(10),
+            Outcome::FixedPoint { generation: 0 }
+        );
+        assert_eq!(sim.grid(), &block);
+
+        let blinker = parse_cells(".....\n.....\n.###.\n.....\n.....\n");
+        let mut sim = Simulation::new(Rule::life(), blinker);
+        assert_eq!(
+            sim.step(),
+            Step {
+                born: 2,
+                died: 2,
+                decayed: 0
+            }
+        );
+        assert_eq!(
+            sim.run_until_stable(10),
+            Outcome::Cycle {
+                start: 1,
+                period: 2
+            }
+        );
+
+        let glider = parse_cells(".#......\n..#.....\n###.....\n........\n");
+        let mut sim = Simulation::new(Rule::life(), glider);
+        assert_eq!(sim.run_until_stable(3), Outcome::StepLimit);
+    }
+
+    #[test]
+    fn test_death_states() {
+        let grid = parse_cells("#.\n..\n");
+
+        let mut transient = Simulation::new(Rule::life(), grid.clone());
+        transient.rule.death = Death::Transient;
+        transient.step();
+        assert_eq!(transient.grid()[(0, 0)], Cell::Removed);
+        assert_eq!(
+            transient.step(),
+            Step {
+                born: 0,
+                died: 0,
+                decayed: 1
+            }
+        );
+        assert_eq!(transient.grid()[(0, 0)], Cell::Dead);
+
+        let mut absorbing = Simulation::new(Rule::life(), grid);
+        absorbing.rule.death = Death::Absorbing;
+        assert_eq!(
+            absorbing.run_until_stable(10),
+            Outcome::FixedPoint { generation: 1 }
+        );
+        assert_eq!(absorbing.grid()[(0, 0)], Cell::Removed);
+
+        // Removed cells are never born again when absorbing.
+        let rule = Rule {
+            birth: vec![0],
+            ..Rule::peeling(&NEIGHBORS8, 9, Death::Absorbing)
+        };
+        assert_eq!(rule.next(Cell::Removed, 0), Cell::Removed);
+        assert_eq!(rule.next(Cell::Dead, 0), Cell::Alive);
+    }
+
+    #[test]
+    fn test_peeling_rule_matches_peel() {
+        let mut rng = Rng::new(31);
+
+        for _ in 0..50 {
+            let width = rng.range(1..10);
+            let height = rng.range(1..10);
+            let grid = Grid::from_fn(width, height, |_, _| {
+                if rng.one_in(4) {
+                    Cell::Dead
+                } else {
+                    Cell::Alive
+                }
+            });
+            let peeling = peel(&grid, &NEIGHBORS8, 4, |&c| c == Cell::Alive);
+
+            let rule = Rule::peeling(&NEIGHBORS8, 4, Death::Transient);
+            let mut sim = Simulation::new(rule, grid);
+            let steps = sim.clone().run(peeling.generations + 1);
+
+            assert_eq!(steps[0].died, peeling.first_wave);
+            assert_eq!(
+                steps.iter().map(|s| s.died).sum::<usize>(),
+                peeling.total_removed
+            );
+            // The last `x` markers need one more generation to decay.
+            let settled = match peeling.generations {
+                0 => 0,
+                g => g + 1,
+            };
+            assert_eq!(
+                sim.run_until_stable(100),
+                Outcome::FixedPoint {
+                    generation: settled
+                }
+            );
+        }
+    }
+}
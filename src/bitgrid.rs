@@ -0,0 +1,342 @@
+use crate::grid::{self, Grid, NEIGHBORS4, NEIGHBORS8};
+
+/// A grid of booleans packed one bit per cell, each row padded to whole `u64` words.
+///
+/// Neighbour counts are computed 64 cells at a time: every stencil offset becomes a shifted
+/// copy of a row, and those are summed with bit-sliced adders, where plane `i` holds bit `i` of
+/// the count of every cell.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitGrid {
+    width: usize,
+    height: usize,
+    words_per_row: usize,
+    // Padding bits past `width` are always 0.
+    words: Vec<u64>,
+}
+
+impl BitGrid {
+    pub fn new(width: usize, height: usize) -> Self {
+        let words_per_row = width.div_ceil(64);
+        Self {
+            width,
+            height,
+            words_per_row,
+            words: vec![0; words_per_row * height],
+        }
+    }
+
+    pub fn from_grid<T>(grid: &Grid<T>, pred: impl Fn(&T) -> bool) -> Self {
+        let mut bits = Self::new(grid.width(), grid.height());
+        for (pos, cell) in grid.iter() {
+            if pred(cell) {
+                bits.set(pos, true);
+            }
+        }
+        bits
+    }
+
+    /// Parses text the same way as [`Grid::parse`], `pred` picking the cells that are set.
+    pub fn parse(input: &str, pred: impl Fn(u8) -> bool) -> Self {
+        Self::from_grid(&Grid::parse(input, pred), |&b| b)
+    }
+
+    pub fn to_grid(&self) -> Grid<bool> {
+        Grid::from_fn(self.width, self.height, |x, y| self.get(x, y).unwrap())
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<bool> {
+        if x < self.width && y < self.height {
+            Some(self.row(y)[x / 64] >> (x % 64) & 1 == 1)
+        } else {
+            None
+        }
+    }
+
+    pub fn set(&mut self, (x, y): (usize, usize), value: bool) {
+        assert!(
+            x < self.width && y < self.height,
+            "position out of the grid"
+        );
+
+        let word = &mut self.words[y * self.words_per_row + x / 64];
+        if value {
+            *word |= 1 << (x % 64);
+        } else {
+            *word &= !(1 << (x % 64));
+        }
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Position at a signed offset from `(x, y)`, if it is inside the grid.
+    pub fn offset(&self, pos: (usize, usize), delta: (isize, isize)) -> Option<(usize, usize)> {
+        grid::offset((self.width, self.height), pos, delta)
+    }
+
+    /// Positions around `pos` along `stencil` that are inside the grid, like
+    /// [`Grid::neighbors`].
+    pub fn neighbors<'a>(
+        &'a self,
+        pos: (usize, usize),
+        stencil: &'a [(isize, isize)],
+    ) -> impl Iterator<Item = (usize, usize)> + 'a {
+        grid::neighbors((self.width, self.height), pos, stencil)
+    }
+
+    pub fn neighbors4(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.neighbors(pos, &NEIGHBORS4)
+    }
+
+    pub fn neighbors8(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.neighbors(pos, &NEIGHBORS8)
+    }
+
+    /// Counts the set neighbours of `pos` in `stencil`, one cell at a time. Use
+    /// [`BitGrid::neighbor_counts`] for the whole grid.
+    pub fn count_neighbors(&self, pos: (usize, usize), stencil: &[(isize, isize)]) -> usize {
+        self.neighbors(pos, stencil)
+            .filter(|&(x, y)| self.get(x, y) == Some(true))
+            .count()
+    }
+
+    /// Cells set in both grids.
+    pub fn and(&self, other: &Self) -> Self {
+        self.zip_words(other, |a, b| a & b)
+    }
+
+    /// Cells set in `self` but not in `other`.
+    pub fn and_not(&self, other: &Self) -> Self {
+        self.zip_words(other, |a, b| a & !b)
+    }
+
+    fn zip_words(&self, other: &Self, f: impl Fn(u64, u64) -> u64) -> Self {
+        assert_eq!((self.width, self.height), (other.width, other.height));
+
+        let words = self
+            .words
+            .iter()
+            .zip(other.words.iter())
+            .map(|(&a, &b)| f(a, b))
+            .collect();
+        Self { words, ..*self }
+    }
+
+    fn row(&self, y: usize) -> &[u64] {
+        &self.words[y * self.words_per_row..(y + 1) * self.words_per_row]
+    }
+
+    /// Mask of the meaningful bits of the last word of a row.
+    fn last_word_mask(&self) -> u64 {
+        match self.width % 64 {
+            0 => u64::MAX,
+            bits => (1 << bits) - 1,
+        }
+    }
+
+    /// Row `y + dy` shifted so that bit `x` holds the cell at `x + dx`, 0 outside the grid.
+    fn shifted_row(&self, y: usize, (dx, dy): (isize, isize), out: &mut [u64]) {
+        out.fill(0);
+        let Some(src) = y
+            .checked_add_signed(dy)
+            .filter(|&y| y < self.height)
+            .map(|y| self.row(y))
+        else {
+            return;
+        };
+
+        let word = |i: isize| -> u64 {
+            usize::try_from(i)
+                .ok()
+                .and_then(|i| src.get(i).copied())
+                .unwrap_or(0)
+        };
+        let (word_shift, bit_shift) = (dx.div_euclid(64), dx.rem_euclid(64) as u32);
+
+        for (i, out) in out.iter_mut().enumerate() {
+            let i = i as isize + word_shift;
+            *out = if bit_shift == 0 {
+                word(i)
+            } else {
+                (word(i) >> bit_shift) | (word(i + 1) << (64 - bit_shift))
+            };
+        }
+
+        if let Some(last) = out.last_mut() {
+            *last &= self.last_word_mask();
+        }
+    }
+
+    /// Bit-sliced neighbour counts of one row: plane `i` holds bit `i` of every cell's count.
+    fn count_planes(&self, y: usize, stencil: &[(isize, isize)], planes: &mut [Vec<u64>]) {
+        let mut shifted = vec![0; self.words_per_row];
+        for plane in planes.iter_mut() {
+            plane.fill(0);
+        }
+
+        for &delta in stencil {
+            self.shifted_row(y, delta, &mut shifted);
+
+            // Ripple-carry add the 1 bit values of `shifted` to the counters, 64 cells at once.
+            for (w, &bits) in shifted.iter().enumerate() {
+                let mut carry = bits;
+                for plane in planes.iter_mut() {
+                    if carry == 0 {
+                        break;
+                    }
+                    let sum = plane[w] ^ carry;
+                    carry &= plane[w];
+                    plane[w] = sum;
+                }
+            }
+        }
+    }
+
+    fn planes_for(stencil: &[(isize, isize)]) -> usize {
+        assert!(
+            stencil.len() <= u8::MAX as usize,
+            "stencil too large for u8 counts"
+        );
+        (usize::BITS - stencil.len().leading_zeros()) as usize
+    }
+
+    /// Counts, for every cell, the set cells among its neighbours in `stencil`.
+    ///
+    /// Gives the same result as [`Grid::neighbor_counts`] on the equivalent `Grid<bool>`.
+    pub fn neighbor_counts(&self, stencil: &[(isize, isize)]) -> Grid<u8> {
+        let mut planes = vec![vec![0; self.words_per_row]; Self::planes_for(stencil)];
+        let mut counts = Grid::new(self.width, self.height, 0);
+
+        for y in 0..self.height {
+            self.count_planes(y, stencil, &mut planes);
+            for x in 0..self.width {
+                counts[(x, y)] = planes.iter().enumerate().fold(0, |acc, (i, plane)| {
+                    acc | (((plane[x / 64] >> (x % 64)) & 1) as u8) << i
+                });
+            }
+        }
+
+        counts
+    }
+
+    /// Cells with fewer than `threshold` set neighbours in `stencil`, without ever leaving the
+    /// bit-sliced representation.
+    pub fn fewer_neighbors_than(&self, stencil: &[(isize, isize)], threshold: usize) -> Self {
+        let n_planes = Self::planes_for(stencil);
+        let mut planes = vec![vec![0; self.words_per_row]; n_planes];
+        let mut out = Self::new(self.width, self.height);
+
+        if threshold > stencil.len() {
+            // Every count is below the threshold.
+            for y in 0..self.height {
+                let row = &mut out.words[y * self.words_per_row..(y + 1) * self.words_per_row];
+                row.fill(u64::MAX);
+                if let Some(last) = row.last_mut() {
+                    *last &= self.last_word_mask();
+                }
+            }
+            return out;
+        }
+
+        for y in 0..self.height {
+            self.count_planes(y, stencil, &mut planes);
+
+            for w in 0..self.words_per_row {
+                // Compare the counts with `threshold` from the most significant bit down.
+                let mut less = 0;
+                let mut equal = u64::MAX;
+                for (i, plane) in planes.iter().enumerate().rev() {
+                    if (threshold >> i) & 1 == 1 {
+                        less |= equal & !plane[w];
+                        equal &= plane[w];
+                    } else {
+                        equal &= !plane[w];
+                    }
+                }
+                out.words[y * self.words_per_row + w] = less;
+            }
+
+            let row = &mut out.words[y * self.words_per_row..(y + 1) * self.words_per_row];
+            if let Some(last) = row.last_mut() {
+                *last &= self.last_word_mask();
+            }
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_get_set() {
+        let mut bits = BitGrid::parse("#..\n.#.\n", |b| b == b'#');
+
+        assert_eq!(bits.get(0, 0), Some(true));
+        assert_eq!(bits.get(1, 0), Some(false));
+        assert_eq!(bits.get(3, 0), None);
+        assert_eq!(bits.count_ones(), 2);
+
+        assert_eq!(
+            bits.neighbors4((0, 0)).collect::<Vec<_>>(),
+            [(1, 0), (0, 1)]
+        );
+        assert_eq!(bits.neighbors8((2, 1)).count(), 3);
+
+        bits.set((2, 1), true);
+        bits.set((0, 0), false);
+        assert_eq!(bits.to_grid(), Grid::parse("...\n.##\n", |b| b == b'#'));
+    }
+
+    #[test]
+    fn test_matches_byte_grid() {
+        let mut rng = Rng::new(32);
+        let wide = [(-70, 0), (65, 1), (0, -2), (-1, 1)];
+
+        for _ in 0..100 {
+            // Widths around word boundaries exercise the carries between words.
+            let width = rng.range(1..200);
+            let height = rng.range(1..8);
+            let grid = Grid::from_fn(width, height, |_, _| rng.one_in(2));
+            let bits = BitGrid::from_grid(&grid, |&b| b);
+
+            for stencil in [&NEIGHBORS4[..], &NEIGHBORS8[..], &wide[..]] {
+                let counts = grid.neighbor_counts(stencil, |&b| b);
+                assert_eq!(bits.neighbor_counts(stencil), counts);
+                for (pos, &count) in counts.iter() {
+                    assert_eq!(bits.count_neighbors(pos, stencil), count as usize);
+                }
+
+                for threshold in 0..=stencil.len() + 1 {
+                    let expected = counts.map(|&c| (c as usize) < threshold);
+                    assert_eq!(
+                        bits.fewer_neighbors_than(stencil, threshold).to_grid(),
+                        expected,
+                        "{stencil:?} < {threshold}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_and_not() {
+        let a = BitGrid::parse("##.\n", |b| b == b'#');
+        let b = BitGrid::parse(".##\n", |b| b == b'#');
+
+        assert_eq!(a.and(&b).to_grid(), Grid::parse(".#.\n", |b| b == b'#'));
+        assert_eq!(a.and_not(&b).to_grid(), Grid::parse("#..\n", |b| b == b'#'));
+    }
+}
@@ -0,0 +1,102 @@
+use std::ops::Sub;
+
+/// Maps a set of sparse values to the dense indices `0..len`, keeping their order.
+///
+/// Handy to shrink huge coordinates down to a small grid where only the distinct values matter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoordCompressor<T> {
+    // Sorted and deduplicated, the index of a value is its compressed coordinate.
+    values: Vec<T>,
+}
+
+impl<T: Ord> CoordCompressor<T> {
+    pub fn new(values: impl IntoIterator<Item = T>) -> Self {
+        let mut values: Vec<T> = values.into_iter().collect();
+        values.sort_unstable();
+        values.dedup();
+        Self { values }
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Compressed coordinate of `value`, `None` if it isn't one of the compressed values.
+    pub fn index_of(&self, value: &T) -> Option<usize> {
+        self.values.binary_search(value).ok()
+    }
+
+    /// Original value of a compressed coordinate.
+    pub fn value_at(&self, index: usize) -> &T {
+        &self.values[index]
+    }
+
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+}
+
+impl<T: Ord + Copy + Sub<Output = T>> CoordCompressor<T> {
+    /// Width of every gap between consecutive values, `widths()[i]` being
+    /// `value_at(i + 1) - value_at(i)`, to weight compressed cells by their real size.
+    pub fn widths(&self) -> Vec<T> {
+        self.values.windows(2).map(|w| w[1] - w[0]).collect()
+    }
+}
+
+impl<T: Ord> FromIterator<T> for CoordCompressor<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::new(iter)
+    }
+}
+
+/// Compresses every axis of a point set at once, returning one compressor per axis and the
+/// compressed points in the same order.
+pub fn compress_points<T: Ord + Copy, const N: usize>(
+    points: &[[T; N]],
+) -> ([CoordCompressor<T>; N], Vec<[usize; N]>) {
+    let axes: [CoordCompressor<T>; N] =
+        std::array::from_fn(|axis| points.iter().map(|p| p[axis]).collect());
+
+    let compressed = points
+        .iter()
+        .map(|p| std::array::from_fn(|axis| axes[axis].index_of(&p[axis]).unwrap()))
+        .collect();
+
+    (axes, compressed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let xs: CoordCompressor<i64> = [100, -5, 7, 100, 7].into_iter().collect();
+
+        assert_eq!(xs.len(), 3);
+        assert_eq!(xs.values(), &[-5, 7, 100]);
+        assert_eq!(xs.index_of(&7), Some(1));
+        assert_eq!(xs.index_of(&8), None);
+        assert_eq!(*xs.value_at(2), 100);
+        assert_eq!(xs.widths(), vec![12, 93]);
+    }
+
+    #[test]
+    fn test_compress_points() {
+        let points = [[162, 817, 812], [57, 618, 57], [906, 360, 560]];
+        let ([xs, ys, zs], compressed) = compress_points(&points);
+
+        assert_eq!(compressed, vec![[1, 2, 2], [0, 1, 0], [2, 0, 1]]);
+        for (p, c) in points.iter().zip(compressed.iter()) {
+            assert_eq!(
+                [*xs.value_at(c[0]), *ys.value_at(c[1]), *zs.value_at(c[2])],
+                *p
+            );
+        }
+    }
+}
@@ -0,0 +1,219 @@
+//! Single-linkage hierarchical clustering, read off the accepted edges of Kruskal's algorithm.
+//!
+//! Clusters are numbered like in SciPy: the leaves are `0..leaves`, and merge `k` creates
+//! cluster `leaves + k`.
+
+use std::fmt::{Display, Write};
+use std::ops::Sub;
+
+use crate::disjoint_set::DisjointSet;
+use crate::mst::{Edge, Event};
+
+/// Two clusters joining into one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Merge<W> {
+    pub left: usize,
+    pub right: usize,
+    /// The edge that joined them, between two leaves.
+    pub edge: Edge<W>,
+    /// Position of that edge among all the edges considered, rejected ones included.
+    pub edge_index: usize,
+    /// Number of leaves in the new cluster.
+    pub size: usize,
+}
+
+/// The merges of single-linkage clustering, in the order they happen.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dendrogram<W> {
+    leaves: usize,
+    merges: Vec<Merge<W>>,
+}
+
+impl<W: Copy> Dendrogram<W> {
+    /// Builds the dendrogram from a stream of Kruskal events, stopping once everything is a
+    /// single cluster.
+    pub fn from_events(leaves: usize, events: impl IntoIterator<Item = Event<W>>) -> Self {
+        let mut set = DisjointSet::new(leaves);
+        // Cluster currently represented by every root of `set`.
+        let mut cluster: Vec<usize> = (0..leaves).collect();
+        let mut merges = Vec::new();
+
+        for (edge_index, event) in events.into_iter().enumerate() {
+            if event.accepted {
+                let (a, b) = (set.find(event.edge.a), set.find(event.edge.b));
+                set.union(a, b);
+                let root = set.find(a);
+                merges.push(Merge {
+                    left: cluster[a],
+                    right: cluster[b],
+                    edge: event.edge,
+                    edge_index,
+                    size: set.size_of(root),
+                });
+                cluster[root] = leaves + merges.len() - 1;
+            }
+            if event.components <= 1 {
+                break;
+            }
+        }
+
+        Self { leaves, merges }
+    }
+
+    pub fn leaves(&self) -> usize {
+        self.leaves
+    }
+
+    pub fn merges(&self) -> &[Merge<W>] {
+        &self.merges
+    }
+
+    /// Clusters once the first `merges` merges are done, as sorted leaf lists, largest first
+    /// and then by smallest leaf.
+    fn clusters_after(&self, merges: usize) -> Vec<Vec<usize>> {
+        let mut set = DisjointSet::new(self.leaves);
+        for merge in &self.merges[..merges] {
+            set.union(merge.edge.a, merge.edge.b);
+        }
+
+        let mut by_root = vec![Vec::new(); self.leaves];
+        for leaf in 0..self.leaves {
+            by_root[set.find(leaf)].push(leaf);
+        }
+
+        let mut clusters: Vec<Vec<usize>> = by_root.into_iter().filter(|c| !c.is_empty()).collect();
+        clusters.sort_by(|a, b| b.len().cmp(&a.len()).then(a[0].cmp(&b[0])));
+        clusters
+    }
+
+    /// Clusters formed by the first `n_edges` edges of the stream.
+    pub fn clusters_at(&self, n_edges: usize) -> Vec<Vec<usize>> {
+        let merges = self.merges.partition_point(|m| m.edge_index < n_edges);
+        self.clusters_after(merges)
+    }
+}
+
+impl<W: Copy + Ord> Dendrogram<W> {
+    /// Clusters formed by every edge of weight at most `max_weight`.
+    pub fn clusters_within(&self, max_weight: W) -> Vec<Vec<usize>> {
+        let merges = self.merges.partition_point(|m| m.edge.weight <= max_weight);
+        self.clusters_after(merges)
+    }
+}
+
+impl<W: Display> Dendrogram<W> {
+    pub fn to_json(&self) -> String {
+        let mut out = format!("{{\"leaves\":{},\"merges\":[", self.leaves);
+        for (k, m) in self.merges.iter().enumerate() {
+            if k > 0 {
+                out.push(',');
+            }
+            write!(
+                out,
+                "{{\"cluster\":{},\"left\":{},\"right\":{},\"weight\":{},\"size\":{},\
+                 \"edge_index\":{}}}",
+                self.leaves + k,
+                m.left,
+                m.right,
+                m.edge.weight,
+                m.size,
+                m.edge_index
+            )
+            .unwrap();
+        }
+        out.push_str("]}");
+        out
+    }
+}
+
+impl<W: Copy + Default + Display + Sub<Output = W>> Dendrogram<W> {
+    /// Newick text, one tree per line if the leaves aren't all connected, with leaves labelled
+    /// by index and branch lengths giving every merge the height of its edge's weight.
+    pub fn to_newick(&self) -> String {
+        // Text and height of every cluster that hasn't been merged yet.
+        let mut trees: Vec<Option<(String, W)>> = (0..self.leaves)
+            .map(|leaf| Some((leaf.to_string(), W::default())))
+            .collect();
+
+        for m in &self.merges {
+            let height = m.edge.weight;
+            let (left, lh) = trees[m.left].take().unwrap();
+            let (right, rh) = trees[m.right].take().unwrap();
+            trees.push(Some((
+                format!("({left}:{},{right}:{})", height - lh, height - rh),
+                height,
+            )));
+        }
+
+        trees
+            .into_iter()
+            .flatten()
+            .map(|(tree, _)| tree + ";\n")
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mst::Kruskal;
+
+    fn dendrogram() -> Dendrogram<u32> {
+        // 0 - 1 - 2 on a line, 3 further away, and 4 on its own.
+        let edges = vec![
+            Edge::new(0, 1, 1),
+            Edge::new(1, 2, 2),
+            Edge::new(0, 2, 3),
+            Edge::new(2, 3, 5),
+        ];
+        Dendrogram::from_events(5, Kruskal::new(5, edges))
+    }
+
+    #[test]
+    fn test_merges() {
+        let d = dendrogram();
+        let merges: Vec<(usize, usize, u32, usize, usize)> = d
+            .merges()
+            .iter()
+            .map(|m| (m.left, m.right, m.edge.weight, m.size, m.edge_index))
+            .collect();
+        assert_eq!(
+            merges,
+            vec![(0, 1, 1, 2, 0), (5, 2, 2, 3, 1), (6, 3, 5, 4, 3)]
+        );
+    }
+
+    #[test]
+    fn test_clusters() {
+        let d = dendrogram();
+
+        assert_eq!(
+            d.clusters_at(0),
+            vec![vec![0], vec![1], vec![2], vec![3], vec![4]]
+        );
+        assert_eq!(d.clusters_at(2), vec![vec![0, 1, 2], vec![3], vec![4]]);
+        // The third edge is rejected and changes nothing.
+        assert_eq!(d.clusters_at(3), d.clusters_at(2));
+        assert_eq!(d.clusters_at(100), vec![vec![0, 1, 2, 3], vec![4]]);
+
+        assert_eq!(
+            d.clusters_within(1),
+            vec![vec![0, 1], vec![2], vec![3], vec![4]]
+        );
+        assert_eq!(d.clusters_within(4), d.clusters_at(2));
+    }
+
+    #[test]
+    fn test_export() {
+        let d = dendrogram();
+
+        assert_eq!(
+            d.to_json(),
+            "{\"leaves\":5,\"merges\":[\
+             {\"cluster\":5,\"left\":0,\"right\":1,\"weight\":1,\"size\":2,\"edge_index\":0},\
+             {\"cluster\":6,\"left\":5,\"right\":2,\"weight\":2,\"size\":3,\"edge_index\":1},\
+             {\"cluster\":7,\"left\":6,\"right\":3,\"weight\":5,\"size\":4,\"edge_index\":3}]}"
+        );
+        assert_eq!(d.to_newick(), "4;\n(((0:1,1:1):1,2:2):3,3:5);\n");
+    }
+}
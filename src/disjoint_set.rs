@@ -0,0 +1,158 @@
+/// Union-find over the elements `0..len`, with path compression and union by size.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DisjointSet {
+    parent: Vec<usize>,
+    // Only meaningful for roots.
+    size: Vec<usize>,
+    components: usize,
+}
+
+impl DisjointSet {
+    /// `len` elements, each in its own component.
+    pub fn new(len: usize) -> Self {
+        Self {
+            parent: (0..len).collect(),
+            size: vec![1; len],
+            components: len,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    /// Adds a new element in its own component, returning it.
+    pub fn push(&mut self) -> usize {
+        let x = self.parent.len();
+        self.parent.push(x);
+        self.size.push(1);
+        self.components += 1;
+        x
+    }
+
+    /// Representative of the component of `x`.
+    pub fn find(&mut self, x: usize) -> usize {
+        let mut root = x;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+
+        // Point the whole path straight at the root.
+        let mut x = x;
+        while self.parent[x] != root {
+            x = std::mem::replace(&mut self.parent[x], root);
+        }
+
+        root
+    }
+
+    /// Merges the components of `a` and `b`, returning `false` if they were already the same.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+
+        if self.size[a] < self.size[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        self.components -= 1;
+        true
+    }
+
+    pub fn same(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// Number of elements in the component of `x`.
+    pub fn size_of(&mut self, x: usize) -> usize {
+        let root = self.find(x);
+        self.size[root]
+    }
+
+    pub fn component_count(&self) -> usize {
+        self.components
+    }
+
+    /// Size of every component, largest first.
+    pub fn component_sizes(&self) -> Vec<usize> {
+        let mut sizes: Vec<usize> = (0..self.len())
+            .filter(|&x| self.parent[x] == x)
+            .map(|root| self.size[root])
+            .collect();
+        sizes.sort_unstable_by(|a, b| b.cmp(a));
+        sizes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_union_find() {
+        let mut set = DisjointSet::new(6);
+        assert_eq!(set.component_count(), 6);
+
+        assert!(set.union(0, 1));
+        assert!(set.union(2, 3));
+        assert!(set.union(1, 3));
+        assert!(!set.union(0, 2));
+
+        assert!(set.same(0, 3));
+        assert!(!set.same(0, 4));
+        assert_eq!(set.size_of(2), 4);
+        assert_eq!(set.size_of(5), 1);
+        assert_eq!(set.component_count(), 3);
+        assert_eq!(set.component_sizes(), vec![4, 1, 1]);
+    }
+
+    #[test]
+    fn test_push() {
+        let mut set = DisjointSet::new(2);
+        assert!(set.union(0, 1));
+
+        assert_eq!(set.push(), 2);
+        assert_eq!(set.len(), 3);
+        assert_eq!(set.component_count(), 2);
+        assert_eq!(set.size_of(2), 1);
+
+        assert!(set.union(2, 0));
+        assert_eq!(set.component_sizes(), vec![3]);
+    }
+
+    #[test]
+    fn test_matches_labels() {
+        // Compare against relabelling every member on merge.
+        let mut rng = Rng::new(43);
+        let n = 50;
+        let mut set = DisjointSet::new(n);
+        let mut labels: Vec<usize> = (0..n).collect();
+
+        for _ in 0..60 {
+            let (a, b) = (rng.range(0..n), rng.range(0..n));
+            let (la, lb) = (labels[a], labels[b]);
+            assert_eq!(set.union(a, b), la != lb);
+            for l in labels.iter_mut().filter(|l| **l == lb) {
+                *l = la;
+            }
+
+            for x in 0..n {
+                let size = labels.iter().filter(|&&l| l == labels[x]).count();
+                assert_eq!(set.size_of(x), size);
+            }
+        }
+
+        let mut distinct = labels.clone();
+        distinct.sort_unstable();
+        distinct.dedup();
+        assert_eq!(set.component_count(), distinct.len());
+    }
+}
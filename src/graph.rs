@@ -0,0 +1,307 @@
+use std::collections::HashMap;
+use std::fmt;
+
+/// Gives every distinct name a dense id, in order of first appearance.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Interner {
+    ids: HashMap<String, usize>,
+    names: Vec<String>,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Id of `name`, a new one if it hasn't been seen yet.
+    pub fn intern(&mut self, name: &str) -> usize {
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+
+        let id = self.names.len();
+        self.ids.insert(name.to_string(), id);
+        self.names.push(name.to_string());
+        id
+    }
+
+    /// Id of `name`, `None` if it was never interned.
+    pub fn get(&self, name: &str) -> Option<usize> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, id: usize) -> &str {
+        &self.names[id]
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+/// A line of an adjacency list without the `:` after the node name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseGraphError {
+    /// 1-based line number.
+    pub line: usize,
+}
+
+impl fmt::Display for ParseGraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: expected `name: a b c`", self.line)
+    }
+}
+
+impl std::error::Error for ParseGraphError {}
+
+/// A directed graph isn't acyclic, as shown by `cycle`: each node has an edge to the next one,
+/// and the last one back to the first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CycleError {
+    pub cycle: Vec<String>,
+}
+
+impl fmt::Display for CycleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cycle: ")?;
+        for name in &self.cycle {
+            write!(f, "{name} -> ")?;
+        }
+        write!(f, "{}", self.cycle[0])
+    }
+}
+
+impl std::error::Error for CycleError {}
+
+/// A directed graph over named nodes, with edges in both directions for quick lookups.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Graph {
+    names: Interner,
+    forward: Vec<Vec<usize>>,
+    reverse: Vec<Vec<usize>>,
+}
+
+impl Graph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses adjacency lists like `aaa: you hhh`, one node per line with an edge to every name
+    /// after the colon. Nodes only named on the right still get an id.
+    pub fn parse(input: &str) -> Result<Self, ParseGraphError> {
+        let mut graph = Self::new();
+
+        for (i, line) in input.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let (from, to) = line
+                .split_once(':')
+                .ok_or(ParseGraphError { line: i + 1 })?;
+            let from = graph.add_node(from.trim());
+            for name in to.split_whitespace() {
+                let to = graph.add_node(name);
+                graph.add_edge(from, to);
+            }
+        }
+
+        Ok(graph)
+    }
+
+    /// Id of the node called `name`, added if needed.
+    pub fn add_node(&mut self, name: &str) -> usize {
+        let id = self.names.intern(name);
+        if id == self.forward.len() {
+            self.forward.push(Vec::new());
+            self.reverse.push(Vec::new());
+        }
+        id
+    }
+
+    pub fn add_edge(&mut self, from: usize, to: usize) {
+        self.forward[from].push(to);
+        self.reverse[to].push(from);
+    }
+
+    /// Number of nodes.
+    pub fn len(&self) -> usize {
+        self.forward.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.forward.is_empty()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.forward.iter().map(Vec::len).sum()
+    }
+
+    /// Id of the node called `name`.
+    pub fn node(&self, name: &str) -> Option<usize> {
+        self.names.get(name)
+    }
+
+    pub fn name(&self, node: usize) -> &str {
+        self.names.name(node)
+    }
+
+    /// Nodes with an edge from `node`, in input order.
+    pub fn successors(&self, node: usize) -> &[usize] {
+        &self.forward[node]
+    }
+
+    /// Nodes with an edge to `node`.
+    pub fn predecessors(&self, node: usize) -> &[usize] {
+        &self.reverse[node]
+    }
+
+    /// Every node after all the nodes with an edge to it (Kahn's algorithm).
+    pub fn topological_order(&self) -> Result<Vec<usize>, CycleError> {
+        let mut in_degree: Vec<usize> = self.reverse.iter().map(Vec::len).collect();
+        let mut order: Vec<usize> = (0..self.len()).filter(|&n| in_degree[n] == 0).collect();
+
+        // `order` doubles as the queue, nodes past `next` haven't been expanded yet.
+        let mut next = 0;
+        while let Some(&node) = order.get(next) {
+            next += 1;
+            for &succ in &self.forward[node] {
+                in_degree[succ] -= 1;
+                if in_degree[succ] == 0 {
+                    order.push(succ);
+                }
+            }
+        }
+
+        if order.len() == self.len() {
+            Ok(order)
+        } else {
+            Err(self.find_cycle(&in_degree))
+        }
+    }
+
+    /// Nodes left with a positive in-degree by Kahn's algorithm all have a predecessor among
+    /// them, so walking predecessors from any of them has to loop.
+    fn find_cycle(&self, in_degree: &[usize]) -> CycleError {
+        let mut seen_at = vec![None; self.len()];
+        let mut walk = Vec::new();
+        let mut node = (0..self.len()).find(|&n| in_degree[n] > 0).unwrap();
+
+        while seen_at[node].is_none() {
+            seen_at[node] = Some(walk.len());
+            walk.push(node);
+            node = *self.reverse[node]
+                .iter()
+                .find(|&&pred| in_degree[pred] > 0)
+                .unwrap();
+        }
+
+        // The walk goes against the edges, reverse the loop it closed.
+        let mut cycle = walk.split_off(seen_at[node].unwrap());
+        cycle.reverse();
+        CycleError {
+            cycle: cycle
+                .into_iter()
+                .map(|n| self.name(n).to_string())
+                .collect(),
+        }
+    }
+
+    /// Number of distinct paths from `from` to `to`, in O(V + E) with one pass over the
+    /// topological order. The whole graph has to be acyclic, not only the part between them.
+    pub fn count_paths(&self, from: usize, to: usize) -> Result<usize, CycleError> {
+        let order = self.topological_order()?;
+
+        let mut paths = vec![0; self.len()];
+        paths[to] = 1;
+        for &node in order.iter().rev() {
+            if node != to {
+                paths[node] = self.forward[node].iter().map(|&succ| paths[succ]).sum();
+            }
+        }
+
+        Ok(paths[from])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interner() {
+        let mut names = Interner::new();
+        assert_eq!(names.intern("you"), 0);
+        assert_eq!(names.intern("out"), 1);
+        assert_eq!(names.intern("you"), 0);
+
+        assert_eq!(names.len(), 2);
+        assert_eq!(names.get("out"), Some(1));
+        assert_eq!(names.get("svr"), None);
+        assert_eq!(names.name(1), "out");
+    }
+
+    #[test]
+    fn test_parse() {
+        let graph = Graph::parse("aaa: you hhh\nyou: bbb\nhhh: out\n").unwrap();
+
+        assert_eq!(graph.len(), 5);
+        assert_eq!(graph.edge_count(), 4);
+
+        let [aaa, you, hhh, out] = ["aaa", "you", "hhh", "out"].map(|n| graph.node(n).unwrap());
+        assert_eq!(graph.successors(aaa), &[you, hhh]);
+        assert_eq!(graph.successors(out), &[]);
+        assert_eq!(graph.predecessors(out), &[hhh]);
+        assert_eq!(graph.predecessors(you), &[aaa]);
+        assert_eq!(graph.name(hhh), "hhh");
+        assert_eq!(graph.node("zzz"), None);
+
+        assert_eq!(
+            Graph::parse("aaa: bbb\nccc ddd\n"),
+            Err(ParseGraphError { line: 2 })
+        );
+    }
+
+    #[test]
+    fn test_topological_order() {
+        let graph = Graph::parse("a: b c\nb: d\nc: d\nd: e\n").unwrap();
+        let order = graph.topological_order().unwrap();
+
+        assert_eq!(order.len(), graph.len());
+        let mut position = vec![0; graph.len()];
+        for (i, &node) in order.iter().enumerate() {
+            position[node] = i;
+        }
+        for node in 0..graph.len() {
+            for &succ in graph.successors(node) {
+                assert!(position[node] < position[succ]);
+            }
+        }
+
+        let [a, d, e] = ["a", "d", "e"].map(|n| graph.node(n).unwrap());
+        assert_eq!(graph.count_paths(a, e), Ok(2));
+        assert_eq!(graph.count_paths(a, d), Ok(2));
+        assert_eq!(graph.count_paths(e, a), Ok(0));
+        assert_eq!(graph.count_paths(d, d), Ok(1));
+    }
+
+    #[test]
+    fn test_cycle() {
+        // `x` leads into the cycle b -> c -> d -> b without being on it.
+        let graph = Graph::parse("x: a\na: b\nb: c\nc: d out\nd: b\n").unwrap();
+        let err = graph.topological_order().unwrap_err();
+
+        let mut cycle = err.cycle.clone();
+        let start = cycle.iter().position(|n| n == "b").unwrap();
+        cycle.rotate_left(start);
+        assert_eq!(cycle, vec!["b", "c", "d"]);
+        assert!(err.to_string().starts_with("cycle: "));
+
+        let [x, out] = ["x", "out"].map(|n| graph.node(n).unwrap());
+        assert_eq!(graph.count_paths(x, out), Err(err));
+    }
+}
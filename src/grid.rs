@@ -0,0 +1,609 @@
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+/// Offsets of the 4 orthogonal neighbours: up, right, down, left.
+pub const NEIGHBORS4: [(isize, isize); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+/// Offsets of the 8 neighbours, diagonals included, row by row.
+pub const NEIGHBORS8: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+/// Moves `(x, y)` by a signed offset, returning `None` if it would leave a `width` by `height`
+/// grid. Shared by every grid-shaped type so they agree on what is out of bounds.
+pub fn offset(
+    (width, height): (usize, usize),
+    (x, y): (usize, usize),
+    (dx, dy): (isize, isize),
+) -> Option<(usize, usize)> {
+    let x = x.checked_add_signed(dx)?;
+    let y = y.checked_add_signed(dy)?;
+    (x < width && y < height).then_some((x, y))
+}
+
+/// Positions around `pos` for an arbitrary stencil of offsets, skipping those outside a `width`
+/// by `height` grid.
+pub fn neighbors(
+    size: (usize, usize),
+    pos: (usize, usize),
+    stencil: &[(isize, isize)],
+) -> impl Iterator<Item = (usize, usize)> + '_ {
+    stencil
+        .iter()
+        .filter_map(move |&delta| offset(size, pos, delta))
+}
+
+/// A dense 2D grid, stored row by row in a single `Vec`.
+///
+/// Positions are `(x, y)` pairs, `x` being the column and `y` the row, with `(0, 0)` in the
+/// top left corner like in the puzzle inputs.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    /// Builds a grid from its cells in row-major order.
+    pub fn from_vec(width: usize, height: usize, cells: Vec<T>) -> Self {
+        assert_eq!(
+            cells.len(),
+            width * height,
+            "cells don't match grid dimensions"
+        );
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    pub fn from_fn(width: usize, height: usize, mut f: impl FnMut(usize, usize) -> T) -> Self {
+        let mut cells = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                cells.push(f(x, y));
+            }
+        }
+        Self::from_vec(width, height, cells)
+    }
+
+    /// Parses a grid of text, mapping every byte to a cell.
+    ///
+    /// The trailing newline is ignored, every line must have the same length.
+    pub fn parse(input: &str, mut f: impl FnMut(u8) -> T) -> Self {
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = Vec::with_capacity(input.len());
+
+        for line in input.lines() {
+            let w = *width.get_or_insert(line.len());
+            assert_eq!(line.len(), w, "line {height} has a different width");
+
+            cells.extend(line.bytes().map(&mut f));
+            height += 1;
+        }
+
+        // Blank lines alone hold no cells, don't count rows that `rows()` can't yield.
+        let width = width.unwrap_or(0);
+        Self::from_vec(width, if width == 0 { 0 } else { height }, cells)
+    }
+
+    /// Parses text whose lines may have different lengths, padding the short ones with `pad`
+    /// to the length of the longest before mapping every byte to a cell.
+    pub fn parse_padded(input: &str, pad: u8, mut f: impl FnMut(u8) -> T) -> Self {
+        let width = input.lines().map(str::len).max().unwrap_or(0);
+        let mut height = 0;
+        let mut cells = Vec::with_capacity(input.len());
+
+        for line in input.lines() {
+            let padding = std::iter::repeat_n(pad, width - line.len());
+            cells.extend(line.bytes().chain(padding).map(&mut f));
+            height += 1;
+        }
+
+        Self::from_vec(width, if width == 0 { 0 } else { height }, cells)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        if x < self.width && y < self.height {
+            Some(&self.cells[y * self.width + x])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        if x < self.width && y < self.height {
+            Some(&mut self.cells[y * self.width + x])
+        } else {
+            None
+        }
+    }
+
+    /// Moves `(x, y)` by a signed offset, returning `None` if it would leave the grid.
+    pub fn offset(&self, pos: (usize, usize), delta: (isize, isize)) -> Option<(usize, usize)> {
+        offset((self.width, self.height), pos, delta)
+    }
+
+    /// Gets the cell at a signed offset from `(x, y)`.
+    pub fn get_offset(&self, pos: (usize, usize), delta: (isize, isize)) -> Option<&T> {
+        let (x, y) = self.offset(pos, delta)?;
+        self.get(x, y)
+    }
+
+    /// Positions around `pos` for an arbitrary stencil of offsets, skipping those outside the grid.
+    pub fn neighbors<'a>(
+        &'a self,
+        pos: (usize, usize),
+        stencil: &'a [(isize, isize)],
+    ) -> impl Iterator<Item = (usize, usize)> + 'a {
+        neighbors((self.width, self.height), pos, stencil)
+    }
+
+    pub fn neighbors4(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.neighbors(pos, &NEIGHBORS4)
+    }
+
+    pub fn neighbors8(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.neighbors(pos, &NEIGHBORS8)
+    }
+
+    /// Counts the neighbours of `pos` in `stencil` whose cell matches `pred`.
+    pub fn count_neighbors(
+        &self,
+        pos: (usize, usize),
+        stencil: &[(isize, isize)],
+        pred: impl Fn(&T) -> bool,
+    ) -> usize {
+        self.neighbors(pos, stencil)
+            .filter(|&pos| pred(&self[pos]))
+            .count()
+    }
+
+    /// Counts, for every cell at once, the neighbours in `stencil` matching `pred`.
+    ///
+    /// Rather than looking around every cell, each matching cell adds itself to the cells
+    /// that see it, so `pred` is only evaluated once per cell.
+    pub fn neighbor_counts(
+        &self,
+        stencil: &[(isize, isize)],
+        pred: impl Fn(&T) -> bool,
+    ) -> Grid<u8> {
+        assert!(
+            stencil.len() <= u8::MAX as usize,
+            "stencil too large for u8 counts"
+        );
+
+        let mut counts = Grid::new(self.width, self.height, 0u8);
+        for (pos, cell) in self.iter() {
+            if !pred(cell) {
+                continue;
+            }
+
+            for &(dx, dy) in stencil {
+                if let Some(seen_by) = self.offset(pos, (-dx, -dy)) {
+                    counts[seen_by] += 1;
+                }
+            }
+        }
+        counts
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // `max(1)` avoids a chunk size of 0, there are no cells anyway in that case.
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        assert!(x < self.width);
+        self.cells.iter().skip(x).step_by(self.width)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(|x| self.column(x))
+    }
+
+    /// All positions, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> + use<T> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    /// All cells with their position, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid::from_vec(self.width, self.height, self.cells.iter().map(f).collect())
+    }
+
+    /// A zero-copy view of the grid, which can then be transposed, rotated or flipped.
+    pub fn view(&self) -> View<'_, T> {
+        View {
+            grid: self,
+            matrix: [[1, 0], [0, 1]],
+            origin: (0, 0),
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    /// Displays the grid using `f` to pick the character of every cell.
+    pub fn display_with<F: Fn(&T) -> char>(&self, f: F) -> DisplayWith<'_, T, F> {
+        DisplayWith { grid: self, f }
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    /// Swaps rows and columns, so that reading the rows of the result reads the columns.
+    pub fn transpose(&self) -> Self {
+        self.view().transposed().to_grid()
+    }
+
+    /// Rotates clockwise, the first row becoming the last column.
+    pub fn rotate_cw(&self) -> Self {
+        self.view().rotated_cw().to_grid()
+    }
+
+    /// Rotates counter-clockwise, the first row becoming the first column (bottom to top).
+    pub fn rotate_ccw(&self) -> Self {
+        self.view().rotated_ccw().to_grid()
+    }
+
+    /// Mirrors left to right.
+    pub fn flip_h(&self) -> Self {
+        self.view().flipped_h().to_grid()
+    }
+
+    /// Mirrors top to bottom.
+    pub fn flip_v(&self) -> Self {
+        self.view().flipped_v().to_grid()
+    }
+}
+
+/// A transposed, rotated or flipped view of a [`Grid`] that doesn't copy any cell.
+///
+/// Positions in the view map to positions in the grid through `matrix * (x, y) + origin`,
+/// so transformations compose without ever touching the cells.
+#[derive(Debug)]
+pub struct View<'a, T> {
+    grid: &'a Grid<T>,
+    matrix: [[isize; 2]; 2],
+    origin: (isize, isize),
+    width: usize,
+    height: usize,
+}
+
+impl<T> Clone for View<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for View<'_, T> {}
+
+impl<'a, T> View<'a, T> {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Position in the underlying grid.
+    fn source(&self, x: usize, y: usize) -> (usize, usize) {
+        let [[a, b], [c, d]] = self.matrix;
+        let (x, y) = (x as isize, y as isize);
+        (
+            (a * x + b * y + self.origin.0) as usize,
+            (c * x + d * y + self.origin.1) as usize,
+        )
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&'a T> {
+        if x < self.width && y < self.height {
+            Some(&self.grid[self.source(x, y)])
+        } else {
+            None
+        }
+    }
+
+    /// New view whose position `(x, y)` is this view's `matrix * (x, y) + offset`.
+    fn compose(
+        &self,
+        [[e, f], [g, h]]: [[isize; 2]; 2],
+        (ox, oy): (isize, isize),
+        swap_dims: bool,
+    ) -> Self {
+        let [[a, b], [c, d]] = self.matrix;
+        let (width, height) = if swap_dims {
+            (self.height, self.width)
+        } else {
+            (self.width, self.height)
+        };
+
+        Self {
+            grid: self.grid,
+            matrix: [
+                [a * e + b * g, a * f + b * h],
+                [c * e + d * g, c * f + d * h],
+            ],
+            origin: (
+                a * ox + b * oy + self.origin.0,
+                c * ox + d * oy + self.origin.1,
+            ),
+            width,
+            height,
+        }
+    }
+
+    pub fn transposed(&self) -> Self {
+        self.compose([[0, 1], [1, 0]], (0, 0), true)
+    }
+
+    pub fn rotated_cw(&self) -> Self {
+        self.compose([[0, 1], [-1, 0]], (0, self.height as isize - 1), true)
+    }
+
+    pub fn rotated_ccw(&self) -> Self {
+        self.compose([[0, -1], [1, 0]], (self.width as isize - 1, 0), true)
+    }
+
+    pub fn flipped_h(&self) -> Self {
+        self.compose([[-1, 0], [0, 1]], (self.width as isize - 1, 0), false)
+    }
+
+    pub fn flipped_v(&self) -> Self {
+        self.compose([[1, 0], [0, -1]], (0, self.height as isize - 1), false)
+    }
+
+    pub fn row(self, y: usize) -> impl Iterator<Item = &'a T> {
+        assert!(y < self.height);
+        (0..self.width).map(move |x| self.get(x, y).unwrap())
+    }
+
+    pub fn rows(self) -> impl Iterator<Item = impl Iterator<Item = &'a T>> {
+        (0..self.height).map(move |y| self.row(y))
+    }
+}
+
+impl<T: Clone> View<'_, T> {
+    pub fn to_grid(&self) -> Grid<T> {
+        Grid::from_fn(self.width, self.height, |x, y| {
+            self.get(x, y).unwrap().clone()
+        })
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        self.get(x, y).expect("position out of the grid")
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        self.get_mut(x, y).expect("position out of the grid")
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returned by [`Grid::display_with`].
+pub struct DisplayWith<'a, T, F> {
+    grid: &'a Grid<T>,
+    f: F,
+}
+
+impl<T, F: Fn(&T) -> char> fmt::Display for DisplayWith<'_, T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.grid.rows() {
+            for cell in row {
+                write!(f, "{}", (self.f)(cell))?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    impl Grid<u8> {
+        fn to_string_lossy(&self) -> String {
+            self.display_with(|&b| b as char).to_string()
+        }
+    }
+
+    const EXAMPLE: &str = "ab.\n.c.\n";
+
+    #[test]
+    fn test_parse_and_get() {
+        let grid = Grid::parse(EXAMPLE, |b| b);
+
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid.get(1, 0), Some(&b'b'));
+        assert_eq!(grid[(1, 1)], b'c');
+        assert_eq!(grid.get(3, 0), None);
+        assert_eq!(grid.get(0, 2), None);
+    }
+
+    #[test]
+    fn test_offset() {
+        let grid = Grid::parse(EXAMPLE, |b| b);
+
+        assert_eq!(grid.get_offset((1, 1), (-1, -1)), Some(&b'a'));
+        assert_eq!(grid.get_offset((0, 0), (-1, 0)), None);
+        assert_eq!(grid.get_offset((2, 1), (1, 0)), None);
+        assert_eq!(grid.offset((2, 1), (-2, -1)), Some((0, 0)));
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        let grid = Grid::parse(EXAMPLE, |b| b);
+
+        assert_eq!(grid.rows().collect::<Vec<_>>(), vec![b"ab.", b".c."]);
+        let columns: Vec<Vec<u8>> = grid.columns().map(|c| c.copied().collect()).collect();
+        assert_eq!(
+            columns,
+            vec![b"a.".to_vec(), b"bc".to_vec(), b"..".to_vec()]
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let grid = Grid::parse(EXAMPLE, |b| b == b'.');
+
+        assert_eq!(
+            grid.display_with(|&empty| if empty { '.' } else { '#' })
+                .to_string(),
+            "##.\n.#.\n"
+        );
+        assert_eq!(grid.map(|&empty| empty as u8).to_string(), "001\n101\n");
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let grid = Grid::new(3, 3, ());
+
+        assert_eq!(
+            grid.neighbors4((0, 0)).collect::<Vec<_>>(),
+            vec![(1, 0), (0, 1)]
+        );
+        assert_eq!(grid.neighbors8((1, 1)).count(), 8);
+        assert_eq!(
+            grid.neighbors8((2, 2)).collect::<Vec<_>>(),
+            vec![(1, 1), (2, 1), (1, 2)]
+        );
+        assert_eq!(
+            grid.neighbors((0, 0), &[(2, 2), (3, 0), (0, -1)])
+                .collect::<Vec<_>>(),
+            vec![(2, 2)]
+        );
+    }
+
+    #[test]
+    fn test_neighbor_counts() {
+        let grid = Grid::parse("@@.\n.@@\n@..\n", |b| b == b'@');
+
+        assert_eq!(grid.count_neighbors((1, 1), &NEIGHBORS8, |&roll| roll), 4);
+        assert_eq!(grid.count_neighbors((0, 0), &NEIGHBORS4, |&roll| roll), 1);
+
+        // An asymmetric stencil: only look at the cell on the right.
+        let right = [(1, 0)];
+        for stencil in [&NEIGHBORS4[..], &NEIGHBORS8[..], &right[..]] {
+            let counts = grid.neighbor_counts(stencil, |&roll| roll);
+            for pos in grid.positions() {
+                let expected = grid.count_neighbors(pos, stencil, |&roll| roll);
+                assert_eq!(counts[pos] as usize, expected, "{pos:?} {stencil:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_padded() {
+        let grid = Grid::parse_padded("ab\nc\n\ndef\n", b' ', |b| b);
+
+        assert_eq!((grid.width(), grid.height()), (3, 4));
+        assert_eq!(grid.to_string_lossy(), "ab \nc  \n   \ndef\n");
+    }
+
+    #[test]
+    fn test_transforms() {
+        let grid = Grid::parse("abc\ndef\n", |b| b);
+
+        assert_eq!(grid.transpose().to_string_lossy(), "ad\nbe\ncf\n");
+        assert_eq!(grid.rotate_cw().to_string_lossy(), "da\neb\nfc\n");
+        assert_eq!(grid.rotate_ccw().to_string_lossy(), "cf\nbe\nad\n");
+        assert_eq!(grid.flip_h().to_string_lossy(), "cba\nfed\n");
+        assert_eq!(grid.flip_v().to_string_lossy(), "def\nabc\n");
+
+        assert_eq!(grid.rotate_cw().rotate_cw(), grid.flip_h().flip_v());
+        assert_eq!(grid.rotate_cw().rotate_ccw(), grid);
+        assert_eq!(grid.transpose().transpose(), grid);
+    }
+
+    #[test]
+    fn test_views_compose() {
+        let grid = Grid::parse("abc\ndef\n", |b| b);
+        let view = grid.view().rotated_cw().flipped_v().transposed();
+
+        assert_eq!(view.to_grid(), grid.rotate_cw().flip_v().transpose());
+        assert_eq!((view.width(), view.height()), (3, 2));
+        assert_eq!(view.get(3, 0), None);
+
+        let rows: Vec<String> = grid
+            .view()
+            .transposed()
+            .rows()
+            .map(|row| row.map(|&b| b as char).collect())
+            .collect();
+        assert_eq!(rows, vec!["ad", "be", "cf"]);
+    }
+
+    #[test]
+    fn test_empty() {
+        let grid = Grid::parse("", |b| b);
+
+        assert_eq!((grid.width(), grid.height()), (0, 0));
+        assert_eq!(grid.rows().count(), 0);
+        assert_eq!(grid.to_string(), "");
+
+        // Blank lines hold no cells, so they make no rows either.
+        for grid in [
+            Grid::parse("\n\n", |b| b),
+            Grid::parse_padded("\n\n", b'.', |b| b),
+        ] {
+            assert_eq!((grid.width(), grid.height()), (0, 0));
+            assert_eq!(grid.rows().count(), grid.height());
+            assert_eq!(grid.to_string(), "");
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::disjoint_set::DisjointSet;
+use crate::point::{Metric, Point};
+
+/// Clusters of points that grow as points are inserted one at a time, every new point being
+/// connected to all the points at most `max_dist` away.
+///
+/// Points are bucketed in a spatial hash whose cells are large enough that a point only needs
+/// to look at its own cell and the adjacent ones, and component sizes are kept in a multiset so
+/// the largest ones can be read after every insertion.
+#[derive(Debug, Clone)]
+pub struct IncrementalClusters<const N: usize> {
+    metric: Metric,
+    max_dist: u128,
+    // Side of the spatial hash cells, no smaller than any coordinate difference between two
+    // connected points.
+    side: i64,
+    points: Vec<Point<i64, N>>,
+    cells: HashMap<[i64; N], Vec<usize>>,
+    set: DisjointSet,
+    // Number of components of every size.
+    sizes: BTreeMap<usize, usize>,
+}
+
+impl<const N: usize> IncrementalClusters<N> {
+    /// Connects points whose `metric` distance is at most `max_dist`, squared for
+    /// [`Metric::Euclidean`].
+    pub fn new(metric: Metric, max_dist: u128) -> Self {
+        let reach = match metric {
+            Metric::Euclidean => max_dist.isqrt(),
+            Metric::Manhattan | Metric::Chebyshev => max_dist,
+        };
+
+        Self {
+            metric,
+            max_dist,
+            side: reach.clamp(1, i64::MAX as u128) as i64,
+            points: Vec::new(),
+            cells: HashMap::new(),
+            set: DisjointSet::new(0),
+            sizes: BTreeMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn points(&self) -> &[Point<i64, N>] {
+        &self.points
+    }
+
+    /// Adds a point and connects it, returning its index.
+    pub fn insert(&mut self, p: Point<i64, N>) -> usize {
+        let index = self.set.push();
+        self.points.push(p);
+        *self.sizes.entry(1).or_default() += 1;
+
+        let cell = p.0.map(|c| c.div_euclid(self.side));
+        let mut neighbors = Vec::new();
+        for k in 0..3usize.pow(N as u32) {
+            // Offsets in -1..=1 on every axis, read off the digits of `k` in base 3.
+            let adjacent: [i64; N] = std::array::from_fn(|axis| {
+                cell[axis] + (k / 3usize.pow(axis as u32) % 3) as i64 - 1
+            });
+            if let Some(bucket) = self.cells.get(&adjacent) {
+                neighbors.extend(
+                    bucket
+                        .iter()
+                        .filter(|&&j| self.metric.distance(p, self.points[j]) <= self.max_dist),
+                );
+            }
+        }
+
+        for j in neighbors {
+            let (a, b) = (self.set.size_of(index), self.set.size_of(j));
+            if self.set.union(index, j) {
+                self.remove_size(a);
+                self.remove_size(b);
+                *self.sizes.entry(a + b).or_default() += 1;
+            }
+        }
+
+        self.cells.entry(cell).or_default().push(index);
+        index
+    }
+
+    fn remove_size(&mut self, size: usize) {
+        let count = self.sizes.get_mut(&size).unwrap();
+        *count -= 1;
+        if *count == 0 {
+            self.sizes.remove(&size);
+        }
+    }
+
+    pub fn component_count(&self) -> usize {
+        self.set.component_count()
+    }
+
+    /// Sizes of the `k` largest components, largest first.
+    pub fn largest(&self, k: usize) -> Vec<usize> {
+        self.sizes
+            .iter()
+            .rev()
+            .flat_map(|(&size, &count)| std::iter::repeat_n(size, count))
+            .take(k)
+            .collect()
+    }
+
+    pub fn same(&mut self, a: usize, b: usize) -> bool {
+        self.set.same(a, b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_matches_recomputing() {
+        let mut rng = Rng::new(48);
+        let points: Vec<Point<i64, 3>> = (0..80)
+            .map(|_| Point(std::array::from_fn(|_| rng.range(0..40) as i64 - 20)))
+            .collect();
+
+        for metric in [Metric::Euclidean, Metric::Manhattan, Metric::Chebyshev] {
+            for max_dist in [0, 7, 50] {
+                let mut clusters = IncrementalClusters::new(metric, max_dist);
+
+                for (n, &p) in points.iter().enumerate() {
+                    clusters.insert(p);
+
+                    let mut set = DisjointSet::new(n + 1);
+                    for i in 0..=n {
+                        for j in i + 1..=n {
+                            if metric.distance(points[i], points[j]) <= max_dist {
+                                set.union(i, j);
+                            }
+                        }
+                    }
+
+                    assert_eq!(clusters.component_count(), set.component_count());
+                    assert_eq!(clusters.largest(usize::MAX), set.component_sizes());
+                    for i in 0..=n {
+                        assert_eq!(clusters.same(i, n), set.same(i, n));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_largest() {
+        let mut clusters = IncrementalClusters::new(Metric::Manhattan, 1);
+        for p in [[0, 0], [1, 0], [5, 5], [9, 9], [9, 8], [-3, 0]] {
+            clusters.insert(Point(p));
+        }
+
+        assert_eq!(clusters.len(), 6);
+        assert_eq!(clusters.component_count(), 4);
+        assert_eq!(clusters.largest(3), vec![2, 2, 1]);
+
+        // Bridging the gap to (-3, 0).
+        clusters.insert(Point([-2, 0]));
+        clusters.insert(Point([-1, 0]));
+        assert_eq!(clusters.largest(2), vec![5, 2]);
+    }
+}
@@ -0,0 +1,324 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::ops::Range;
+
+use crate::mst::Edge;
+use crate::point::{Metric, Point};
+
+/// A static k-d tree for nearest neighbour and radius queries.
+///
+/// Queries give `(distance, index)` pairs, the index being the position of the point in
+/// the slice the tree was built from. Results are sorted by distance then index, so ties always
+/// come out the same way. Distances are squared Euclidean ones unless the tree is built with
+/// another [`Metric`].
+#[derive(Debug, Clone)]
+pub struct KdTree<T, const N: usize> {
+    points: Vec<Point<T, N>>,
+    metric: Metric,
+    // A balanced tree laid out in place: the node of `order[range]` is in the middle of the
+    // range, splitting on axis `depth % N`, with its left subtree before it and its right one
+    // after.
+    order: Vec<usize>,
+}
+
+/// What a search keeps of the points it visits.
+trait Collector {
+    /// Largest distance still worth visiting, `None` if any is.
+    fn limit(&self) -> Option<u128>;
+    fn offer(&mut self, dist: u128, index: usize);
+}
+
+struct Nearest {
+    k: usize,
+    // Max-heap of the best candidates so far.
+    best: BinaryHeap<(u128, usize)>,
+}
+
+impl Collector for Nearest {
+    fn limit(&self) -> Option<u128> {
+        (self.best.len() == self.k).then(|| self.best.peek().unwrap().0)
+    }
+
+    fn offer(&mut self, dist: u128, index: usize) {
+        if self.best.len() < self.k {
+            self.best.push((dist, index));
+        } else if self.best.peek().is_some_and(|&worst| (dist, index) < worst) {
+            self.best.pop();
+            self.best.push((dist, index));
+        }
+    }
+}
+
+struct Radius {
+    max: u128,
+    found: Vec<(u128, usize)>,
+}
+
+impl Collector for Radius {
+    fn limit(&self) -> Option<u128> {
+        Some(self.max)
+    }
+
+    fn offer(&mut self, dist: u128, index: usize) {
+        if dist <= self.max {
+            self.found.push((dist, index));
+        }
+    }
+}
+
+impl<T: Copy + Ord + Into<i128>, const N: usize> KdTree<T, N> {
+    pub fn new(points: &[Point<T, N>]) -> Self {
+        Self::with_metric(points, Metric::Euclidean)
+    }
+
+    pub fn with_metric(points: &[Point<T, N>], metric: Metric) -> Self {
+        let mut order: Vec<usize> = (0..points.len()).collect();
+        build(points, &mut order, 0);
+        Self {
+            points: points.to_vec(),
+            metric,
+            order,
+        }
+    }
+
+    pub fn metric(&self) -> Metric {
+        self.metric
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn points(&self) -> &[Point<T, N>] {
+        &self.points
+    }
+
+    /// The `k` points closest to `query`, closest first, including `query` itself if it is one
+    /// of the points.
+    pub fn nearest(&self, query: Point<T, N>, k: usize) -> Vec<(u128, usize)> {
+        // `usize::MAX` asks for every point, which mustn't size the heap.
+        let k = k.min(self.len());
+        if k == 0 {
+            return Vec::new();
+        }
+
+        let mut nearest = Nearest {
+            k,
+            best: BinaryHeap::with_capacity(k + 1),
+        };
+        self.search(0..self.order.len(), 0, query, &mut nearest);
+        nearest.best.into_sorted_vec()
+    }
+
+    /// Every point at most `max_dist` away from `query`, closest first.
+    pub fn within(&self, query: Point<T, N>, max_dist: u128) -> Vec<(u128, usize)> {
+        let mut radius = Radius {
+            max: max_dist,
+            found: Vec::new(),
+        };
+        self.search(0..self.order.len(), 0, query, &mut radius);
+        radius.found.sort_unstable();
+        radius.found
+    }
+
+    fn search(
+        &self,
+        range: Range<usize>,
+        depth: usize,
+        query: Point<T, N>,
+        collector: &mut impl Collector,
+    ) {
+        if range.is_empty() {
+            return;
+        }
+
+        let mid = range.start + range.len() / 2;
+        let index = self.order[mid];
+        let pivot = self.points[index];
+        collector.offer(self.metric.distance(query, pivot), index);
+
+        let axis = depth % N;
+        let diff = query[axis].into() - pivot[axis].into();
+        let (left, right) = (range.start..mid, mid + 1..range.end);
+        let (near, far) = if diff <= 0 {
+            (left, right)
+        } else {
+            (right, left)
+        };
+
+        self.search(near, depth + 1, query, collector);
+
+        // Anything on the far side is at least as far as the splitting plane.
+        let plane = self.metric.axis_bound(diff.unsigned_abs());
+        if collector.limit().is_none_or(|limit| plane <= limit) {
+            self.search(far, depth + 1, query, collector);
+        }
+    }
+}
+
+fn build<T: Copy + Ord, const N: usize>(points: &[Point<T, N>], order: &mut [usize], depth: usize) {
+    if order.len() <= 1 {
+        return;
+    }
+
+    let mid = order.len() / 2;
+    let axis = depth % N;
+    order.select_nth_unstable_by_key(mid, |&i| points[i][axis]);
+
+    let (left, right) = order.split_at_mut(mid);
+    build(points, left, depth + 1);
+    build(points, &mut right[1..], depth + 1);
+}
+
+/// Every pair of points as an [`Edge`] weighted by their distance, in increasing order of
+/// `(weight, a, b)` with `a < b`, without ever holding all the pairs.
+///
+/// Every point walks through its neighbours from the closest, fetched from a k-d tree in
+/// batches that double in size, and a heap merges those walks. Only the pairs that come out
+/// and a few neighbours per point are ever computed, which suits Kruskal-like consumers that
+/// stop early.
+pub fn pairs_by_distance<T: Copy + Ord + Into<i128>, const N: usize>(
+    points: &[Point<T, N>],
+    metric: Metric,
+) -> PairsByDistance<T, N> {
+    let mut pairs = PairsByDistance {
+        tree: KdTree::with_metric(points, metric),
+        neighbors: vec![(Vec::new(), 0); points.len()],
+        heap: BinaryHeap::new(),
+        last: None,
+    };
+    for i in 0..points.len() {
+        pairs.advance(i);
+    }
+    pairs
+}
+
+/// Iterator returned by [`pairs_by_distance`].
+pub struct PairsByDistance<T, const N: usize> {
+    tree: KdTree<T, N>,
+    // The nearest neighbours of every point fetched so far, and how many were walked through.
+    neighbors: Vec<(Vec<(u128, usize)>, usize)>,
+    // The next pair of every point that still has one, as `(weight, a, b, point)`.
+    heap: BinaryHeap<Reverse<(u128, usize, usize, usize)>>,
+    last: Option<(u128, usize, usize)>,
+}
+
+impl<T: Copy + Ord + Into<i128>, const N: usize> PairsByDistance<T, N> {
+    /// Queues the next neighbour of point `i`, if any.
+    fn advance(&mut self, i: usize) {
+        loop {
+            let (fetched, walked) = &mut self.neighbors[i];
+            if let Some(&(dist, j)) = fetched.get(*walked) {
+                *walked += 1;
+                if j != i {
+                    self.heap.push(Reverse((dist, i.min(j), i.max(j), i)));
+                    return;
+                }
+            } else if fetched.len() == self.tree.len() {
+                return;
+            } else {
+                let k = (2 * fetched.len()).max(8).min(self.tree.len());
+                *fetched = self.tree.nearest(self.tree.points[i], k);
+            }
+        }
+    }
+}
+
+impl<T: Copy + Ord + Into<i128>, const N: usize> Iterator for PairsByDistance<T, N> {
+    type Item = Edge<u128>;
+
+    fn next(&mut self) -> Option<Edge<u128>> {
+        loop {
+            let Reverse((dist, a, b, from)) = self.heap.pop()?;
+            self.advance(from);
+
+            // Both ends of a pair find it, keys come out increasing so the second one is
+            // never greater than the last pair returned.
+            let key = (dist, a, b);
+            if self.last.is_some_and(|last| key <= last) {
+                continue;
+            }
+            self.last = Some(key);
+            return Some(Edge::new(a, b, dist));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    fn random_points(seed: u64, n: usize, range: usize) -> Vec<Point<i64, 3>> {
+        let mut rng = Rng::new(seed);
+        (0..n)
+            .map(|_| Point(std::array::from_fn(|_| rng.range(0..range) as i64 - 5)))
+            .collect()
+    }
+
+    const METRICS: [Metric; 3] = [Metric::Euclidean, Metric::Manhattan, Metric::Chebyshev];
+
+    fn brute_force(
+        points: &[Point<i64, 3>],
+        query: Point<i64, 3>,
+        metric: Metric,
+    ) -> Vec<(u128, usize)> {
+        let mut all: Vec<(u128, usize)> = points
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (metric.distance(query, *p), i))
+            .collect();
+        all.sort_unstable();
+        all
+    }
+
+    #[test]
+    fn test_queries_match_brute_force() {
+        // A small range makes lots of ties and duplicate points.
+        for (seed, range) in [(1, 6), (2, 50), (3, 1000)] {
+            let points = random_points(seed, 120, range);
+            let queries = random_points(seed + 100, 20, range + 4);
+
+            for metric in METRICS {
+                let tree = KdTree::with_metric(&points, metric);
+                for query in points.iter().chain(queries.iter()).copied() {
+                    let all = brute_force(&points, query, metric);
+                    for k in [0, 1, 5, 17, 120, 200, usize::MAX] {
+                        assert_eq!(tree.nearest(query, k), all[..k.min(all.len())]);
+                    }
+                    for max in [0, 3, 40, 1000] {
+                        let within: Vec<_> =
+                            all.iter().copied().filter(|&(d, _)| d <= max).collect();
+                        assert_eq!(tree.within(query, max), within);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_pairs_by_distance() {
+        for (seed, n, range) in [(4, 0, 10), (5, 1, 10), (6, 2, 1), (7, 60, 4), (8, 90, 300)] {
+            let points = random_points(seed, n, range);
+
+            for metric in METRICS {
+                let mut expected = Vec::new();
+                for i in 0..n {
+                    for j in i + 1..n {
+                        expected.push((metric.distance(points[i], points[j]), i, j));
+                    }
+                }
+                expected.sort_unstable();
+
+                let pairs: Vec<_> = pairs_by_distance(&points, metric)
+                    .map(|e| (e.weight, e.a, e.b))
+                    .collect();
+                assert_eq!(pairs, expected);
+            }
+        }
+    }
+}
@@ -1,7 +1,29 @@
+use std::fmt;
 use std::iter::FromIterator;
 use std::ops::Range;
 use std::ops::RangeInclusive;
 
+pub mod automaton;
+pub mod bitgrid;
+pub mod compress;
+pub mod dendrogram;
+pub mod disjoint_set;
+pub mod graph;
+pub mod grid;
+pub mod incremental;
+pub mod kdtree;
+pub mod mst;
+pub mod point;
+pub mod polygon;
+pub mod prefix_sum;
+pub mod render;
+pub mod rng;
+pub mod search;
+pub mod sparse_grid;
+pub mod svg;
+
+pub use grid::Grid;
+
 /// Loads input for a specific Advent of Code day.
 ///
 /// Arguments:
@@ -29,6 +51,30 @@ pub struct IntervalSet<T> {
     inner: Vec<T>,
 }
 
+/// Reasons why a list of boundaries is not a valid `IntervalSet`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvariantViolation {
+    /// The boundaries don't pair up into start/end couples.
+    OddLength(usize),
+    /// The boundary at `index` is not strictly greater than the one before it.
+    NotStrictlyIncreasing { index: usize },
+}
+
+impl fmt::Display for InvariantViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvariantViolation::OddLength(len) => {
+                write!(f, "expected an even number of boundaries, got {len}")
+            }
+            InvariantViolation::NotStrictlyIncreasing { index } => {
+                write!(f, "boundary at index {index} is not strictly increasing")
+            }
+        }
+    }
+}
+
+impl std::error::Error for InvariantViolation {}
+
 impl<T> IntervalSet<T> {
     pub fn new() -> Self {
         Self { inner: Vec::new() }
@@ -36,6 +82,36 @@ impl<T> IntervalSet<T> {
 }
 
 impl<T: Ord> IntervalSet<T> {
+    /// Builds a set directly from its boundaries in O(n), e.g. `[2, 5, 7, 9]` for `2..5` and `7..9`.
+    ///
+    /// The boundaries must be strictly increasing and of even length, adjacent ranges such as
+    /// `[1, 3, 3, 5]` are rejected since they would have been merged by `insert`.
+    pub fn from_sorted_boundaries(boundaries: Vec<T>) -> Result<Self, InvariantViolation> {
+        let set = Self { inner: boundaries };
+        set.check_invariants()?;
+        Ok(set)
+    }
+
+    /// Checks that the boundaries have an even length and are strictly increasing.
+    pub fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        if !self.inner.len().is_multiple_of(2) {
+            return Err(InvariantViolation::OddLength(self.inner.len()));
+        }
+
+        match self.inner.windows(2).position(|w| w[0] >= w[1]) {
+            Some(i) => Err(InvariantViolation::NotStrictlyIncreasing { index: i + 1 }),
+            None => Ok(()),
+        }
+    }
+
+    #[inline]
+    fn debug_check_invariants(&self) {
+        #[cfg(debug_assertions)]
+        if let Err(err) = self.check_invariants() {
+            panic!("IntervalSet invariant violated: {err}");
+        }
+    }
+
     /// Checks if x is contained within any interval.
     pub fn contains(&self, x: &T) -> bool {
         match self.inner.binary_search(x) {
@@ -85,6 +161,103 @@ impl<T: Ord> IntervalSet<T> {
         // 4. Replace the affected range in the vector.
         // We remove everything between the conceptual new start and new end.
         self.inner.splice(start_idx..end_idx, new_items);
+
+        self.debug_check_invariants();
+    }
+
+    /// Removes a range, splitting any interval that straddles its bounds.
+    pub fn remove(&mut self, range: Range<T>) {
+        assert!(range.start < range.end);
+
+        // 1. Determine where the removal starts affecting the vector
+        // and if we need to insert a new end point to cut an interval short.
+        let (start_idx, push_start) = match self.inner.binary_search(&range.start) {
+            Ok(i) if i % 2 == 0 => (i, false), // Hit existing Start: Remove it.
+            Ok(i) => (i + 1, false),           // Hit existing End: Keep it.
+            Err(i) if i % 2 == 0 => (i, false), // In a gap: Nothing to cut.
+            Err(i) => (i, true),               // Inside interval: Insert new End.
+        };
+
+        // 2. Determine where the removal stops affecting the vector
+        // and if we need to insert a new start point to resume an interval.
+        let (end_idx, push_end) = match self.inner.binary_search(&range.end) {
+            Ok(i) if i % 2 == 0 => (i, false),  // Hit existing Start: Keep it.
+            Ok(i) => (i + 1, false),            // Hit existing End: Remove it.
+            Err(i) if i % 2 == 0 => (i, false), // In a gap: Nothing to resume.
+            Err(i) => (i, true),                // Inside interval: Insert new Start.
+        };
+
+        let mut new_items = Vec::with_capacity(2);
+        if push_start {
+            new_items.push(range.start);
+        }
+        if push_end {
+            new_items.push(range.end);
+        }
+
+        self.inner.splice(start_idx..end_idx, new_items);
+
+        self.debug_check_invariants();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+}
+
+impl<T: Ord + Clone> IntervalSet<T> {
+    /// Iterates over the disjoint ranges of the set, in increasing order.
+    pub fn ranges(&self) -> impl Iterator<Item = Range<T>> + '_ {
+        self.inner
+            .chunks_exact(2)
+            .map(|chunk| chunk[0].clone()..chunk[1].clone())
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        self.combine(other, |a, b| a || b)
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        self.combine(other, |a, b| a && b)
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        self.combine(other, |a, b| a && !b)
+    }
+
+    /// Sweeps over the boundaries of both sets in a single pass, emitting a boundary
+    /// every time `op` applied to the membership of both sets changes.
+    fn combine(&self, other: &Self, op: impl Fn(bool, bool) -> bool) -> Self {
+        let (a, b) = (&self.inner, &other.inner);
+        let (mut i, mut j) = (0, 0);
+        let mut inner = Vec::with_capacity(a.len() + b.len());
+        let mut included = false;
+
+        while i < a.len() || j < b.len() {
+            // Step over the smallest boundary, on both sides at once when they coincide.
+            let x = match (a.get(i), b.get(j)) {
+                (Some(x), Some(y)) if x < y => x,
+                (Some(x), Some(y)) if y < x => y,
+                (Some(x), _) | (None, Some(x)) => x,
+                (None, None) => unreachable!(),
+            };
+            if a.get(i) == Some(x) {
+                i += 1;
+            }
+            if b.get(j) == Some(x) {
+                j += 1;
+            }
+
+            // An odd number of boundaries passed means we are inside an interval.
+            if op(i % 2 != 0, j % 2 != 0) != included {
+                included = !included;
+                inner.push(x.clone());
+            }
+        }
+
+        let set = Self { inner };
+        set.debug_check_invariants();
+        set
     }
 }
 
@@ -225,4 +398,301 @@ mod tests {
         assert_eq!(set.inner, vec![1, 8, 10, 20]);
         assert_eq!(set.count(), 17); // (8-1) + (20-10) = 7 + 10 = 17
     }
+
+    #[test]
+    fn test_from_sorted_boundaries() {
+        let set = IntervalSet::from_sorted_boundaries(vec![2, 5, 7, 9]).unwrap();
+        assert!(set.contains(&2));
+        assert!(!set.contains(&5));
+        assert!(set.contains(&8));
+        assert_eq!(set.count(), 5);
+        assert_eq!(set.check_invariants(), Ok(()));
+
+        assert_eq!(
+            IntervalSet::from_sorted_boundaries(vec![2, 5, 7]).unwrap_err(),
+            InvariantViolation::OddLength(3)
+        );
+        assert_eq!(
+            IntervalSet::from_sorted_boundaries(vec![2, 5, 5, 9]).unwrap_err(),
+            InvariantViolation::NotStrictlyIncreasing { index: 2 }
+        );
+        assert_eq!(
+            IntervalSet::from_sorted_boundaries(vec![5, 2]).unwrap_err(),
+            InvariantViolation::NotStrictlyIncreasing { index: 1 }
+        );
+    }
+}
+
+#[cfg(test)]
+mod property_tests {
+    //! Randomized tests comparing `IntervalSet` against a naive `BTreeSet` model.
+
+    use super::*;
+    use crate::rng::Rng;
+    use std::collections::BTreeSet;
+    use std::panic::{AssertUnwindSafe, catch_unwind};
+
+    /// Values are drawn from `0..UNIVERSE` so that ranges often overlap and touch.
+    const UNIVERSE: usize = 48;
+    const CASES: u64 = 3000;
+    const MAX_OPS: usize = 24;
+
+    #[derive(Debug, Clone)]
+    enum Op {
+        Insert(Range<usize>),
+        InsertInclusive(RangeInclusive<usize>),
+        Remove(Range<usize>),
+        Union(Vec<Range<usize>>),
+        Intersection(Vec<Range<usize>>),
+        Difference(Vec<Range<usize>>),
+    }
+
+    fn random_range(rng: &mut Rng) -> Range<usize> {
+        let start = rng.range(0..UNIVERSE - 1);
+        // Mostly short ranges, sometimes long ones spanning several intervals.
+        let max_len = if rng.one_in(4) {
+            UNIVERSE - start
+        } else {
+            6.min(UNIVERSE - start)
+        };
+        start..start + rng.range(1..max_len + 1)
+    }
+
+    fn random_ranges(rng: &mut Rng) -> Vec<Range<usize>> {
+        let n = rng.range(0..5);
+        (0..n).map(|_| random_range(rng)).collect()
+    }
+
+    fn random_op(rng: &mut Rng) -> Op {
+        match rng.range(0..8) {
+            0..=2 => Op::Insert(random_range(rng)),
+            3 => {
+                let r = random_range(rng);
+                Op::InsertInclusive(r.start..=r.end - 1)
+            }
+            4 | 5 => Op::Remove(random_range(rng)),
+            6 => match rng.range(0..3) {
+                0 => Op::Union(random_ranges(rng)),
+                1 => Op::Intersection(random_ranges(rng)),
+                _ => Op::Difference(random_ranges(rng)),
+            },
+            _ => Op::Union(random_ranges(rng)),
+        }
+    }
+
+    fn to_model(ranges: &[Range<usize>]) -> BTreeSet<usize> {
+        ranges.iter().cloned().flatten().collect()
+    }
+
+    fn apply(set: &mut IntervalSet<usize>, model: &mut BTreeSet<usize>, op: &Op) {
+        match op {
+            Op::Insert(r) => {
+                set.insert(r.clone());
+                model.extend(r.clone());
+            }
+            Op::InsertInclusive(r) => {
+                set.insert_inclusive(r.clone());
+                model.extend(r.clone());
+            }
+            Op::Remove(r) => {
+                set.remove(r.clone());
+                for x in r.clone() {
+                    model.remove(&x);
+                }
+            }
+            Op::Union(rs) => {
+                *set = set.union(&rs.iter().cloned().collect());
+                *model = model.union(&to_model(rs)).copied().collect();
+            }
+            Op::Intersection(rs) => {
+                *set = set.intersection(&rs.iter().cloned().collect());
+                *model = model.intersection(&to_model(rs)).copied().collect();
+            }
+            Op::Difference(rs) => {
+                *set = set.difference(&rs.iter().cloned().collect());
+                *model = model.difference(&to_model(rs)).copied().collect();
+            }
+        }
+    }
+
+    /// Maximal runs of consecutive values, which is what the ranges of the set must be.
+    fn model_ranges(model: &BTreeSet<usize>) -> Vec<Range<usize>> {
+        let mut ranges: Vec<Range<usize>> = Vec::new();
+        for &x in model {
+            match ranges.last_mut() {
+                Some(last) if last.end == x => last.end += 1,
+                _ => ranges.push(x..x + 1),
+            }
+        }
+        ranges
+    }
+
+    fn compare(set: &IntervalSet<usize>, model: &BTreeSet<usize>) -> Result<(), String> {
+        set.check_invariants().map_err(|err| err.to_string())?;
+
+        for x in 0..UNIVERSE + 2 {
+            if set.contains(&x) != model.contains(&x) {
+                return Err(format!("contains({x}) = {}", set.contains(&x)));
+            }
+        }
+
+        if set.count() != model.len() {
+            return Err(format!(
+                "count() = {}, expected {}",
+                set.count(),
+                model.len()
+            ));
+        }
+
+        let ranges: Vec<_> = set.ranges().collect();
+        let expected = model_ranges(model);
+        if ranges != expected {
+            return Err(format!("ranges() = {ranges:?}, expected {expected:?}"));
+        }
+
+        Ok(())
+    }
+
+    /// Runs a sequence of operations, returning the first mismatch (or panic) found.
+    fn run(ops: &[Op]) -> Result<(), String> {
+        let mut set = IntervalSet::new();
+        let mut model = BTreeSet::new();
+
+        for (i, op) in ops.iter().enumerate() {
+            catch_unwind(AssertUnwindSafe(|| apply(&mut set, &mut model, op)))
+                .map_err(|_| format!("op {i} panicked"))?;
+            compare(&set, &model).map_err(|err| format!("after op {i}: {err}"))?;
+        }
+
+        Ok(())
+    }
+
+    fn shrink_range(r: &Range<usize>) -> Vec<Range<usize>> {
+        let mut out = Vec::new();
+        if r.start + 1 < r.end {
+            out.push(r.start + 1..r.end);
+            out.push(r.start..r.end - 1);
+        }
+        if r.start > 0 {
+            out.push(r.start - 1..r.end - 1);
+        }
+        out
+    }
+
+    fn shrink_ranges(rs: &[Range<usize>]) -> Vec<Vec<Range<usize>>> {
+        let mut out = Vec::new();
+        for i in 0..rs.len() {
+            let mut smaller = rs.to_vec();
+            smaller.remove(i);
+            out.push(smaller);
+
+            for r in shrink_range(&rs[i]) {
+                let mut smaller = rs.to_vec();
+                smaller[i] = r;
+                out.push(smaller);
+            }
+        }
+        out
+    }
+
+    fn shrink_op(op: &Op) -> Vec<Op> {
+        match op {
+            Op::Insert(r) => shrink_range(r).into_iter().map(Op::Insert).collect(),
+            Op::InsertInclusive(r) => shrink_range(&(*r.start()..*r.end() + 1))
+                .into_iter()
+                .map(|r| Op::InsertInclusive(r.start..=r.end - 1))
+                .chain([Op::Insert(*r.start()..*r.end() + 1)])
+                .collect(),
+            Op::Remove(r) => shrink_range(r).into_iter().map(Op::Remove).collect(),
+            Op::Union(rs) => shrink_ranges(rs).into_iter().map(Op::Union).collect(),
+            Op::Intersection(rs) => shrink_ranges(rs)
+                .into_iter()
+                .map(Op::Intersection)
+                .chain([Op::Union(rs.clone())])
+                .collect(),
+            Op::Difference(rs) => shrink_ranges(rs)
+                .into_iter()
+                .map(Op::Difference)
+                .chain([Op::Union(rs.clone())])
+                .collect(),
+        }
+    }
+
+    /// Greedily shrinks a sequence failing `check`: drop operations first, then simplify
+    /// the remaining ones, until no single step keeps it failing.
+    fn shrink(mut ops: Vec<Op>, check: impl Fn(&[Op]) -> Result<(), String>) -> (Vec<Op>, String) {
+        let mut err = check(&ops).unwrap_err();
+
+        'progress: loop {
+            let removals = (0..ops.len()).map(|i| {
+                let mut candidate = ops.clone();
+                candidate.remove(i);
+                candidate
+            });
+            let simplifications = (0..ops.len()).flat_map(|i| {
+                let ops = &ops;
+                shrink_op(&ops[i]).into_iter().map(move |op| {
+                    let mut candidate = ops.clone();
+                    candidate[i] = op;
+                    candidate
+                })
+            });
+
+            for candidate in removals.chain(simplifications) {
+                if let Err(e) = check(&candidate) {
+                    (ops, err) = (candidate, e);
+                    continue 'progress;
+                }
+            }
+
+            return (ops, err);
+        }
+    }
+
+    #[test]
+    fn test_matches_btreeset_model() {
+        for seed in 0..CASES {
+            let mut rng = Rng::new(seed);
+            let len = rng.range(1..MAX_OPS + 1);
+            let ops: Vec<Op> = (0..len).map(|_| random_op(&mut rng)).collect();
+
+            if run(&ops).is_err() {
+                let (ops, err) = shrink(ops, run);
+                panic!("seed {seed} failed ({err}), minimal sequence: {ops:#?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_shrinking_finds_minimal_sequence() {
+        // Pretend that containing 5 is a bug, the minimal reproduction is inserting just 5.
+        let contains_five = |ops: &[Op]| {
+            let mut set = IntervalSet::new();
+            let mut model = BTreeSet::new();
+            for op in ops {
+                apply(&mut set, &mut model, op);
+            }
+            if set.contains(&5) {
+                Err("contains 5".to_string())
+            } else {
+                Ok(())
+            }
+        };
+
+        let mut rng = Rng::new(1);
+        let ops = loop {
+            let ops: Vec<Op> = (0..MAX_OPS).map(|_| random_op(&mut rng)).collect();
+            if contains_five(&ops).is_err() {
+                break ops;
+            }
+        };
+
+        let (ops, _) = shrink(ops, contains_five);
+        match ops.as_slice() {
+            [Op::Insert(r)] => assert_eq!(*r, 5..6),
+            [Op::InsertInclusive(r)] => assert_eq!(*r, 5..=5),
+            [Op::Union(rs)] => assert_eq!(*rs, vec![5..6]),
+            _ => panic!("not minimal: {ops:?}"),
+        }
+    }
 }
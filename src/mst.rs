@@ -0,0 +1,193 @@
+use std::iter::Sum;
+
+use crate::disjoint_set::DisjointSet;
+
+/// An undirected edge between the vertices `a` and `b`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Edge<W> {
+    pub a: usize,
+    pub b: usize,
+    pub weight: W,
+}
+
+impl<W> Edge<W> {
+    pub fn new(a: usize, b: usize, weight: W) -> Self {
+        Self { a, b, weight }
+    }
+}
+
+/// What Kruskal's algorithm did with an edge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Event<W> {
+    pub edge: Edge<W>,
+    /// Whether the edge joined two components, i.e. is part of the spanning forest.
+    pub accepted: bool,
+    /// Number of components once the edge is processed.
+    pub components: usize,
+    /// Size of the largest component once the edge is processed, see
+    /// [`Kruskal::components`] and [`DisjointSet::component_sizes`] for the others.
+    pub largest: usize,
+}
+
+/// Kruskal's algorithm as an iterator, yielding an [`Event`] per edge in weight order.
+///
+/// The components can be inspected between events through [`Kruskal::components`], e.g. to
+/// get every component size after the first `n` edges.
+pub struct Kruskal<I> {
+    edges: I,
+    components: DisjointSet,
+    largest: usize,
+}
+
+impl<W: Copy + Ord> Kruskal<std::vec::IntoIter<Edge<W>>> {
+    /// Sorts the edges by weight, equal weights keeping their order.
+    pub fn new(vertices: usize, edges: impl IntoIterator<Item = Edge<W>>) -> Self {
+        let mut edges: Vec<Edge<W>> = edges.into_iter().collect();
+        edges.sort_by_key(|e| e.weight);
+        Self::from_sorted(vertices, edges)
+    }
+}
+
+impl<W, I: Iterator<Item = Edge<W>>> Kruskal<I> {
+    /// Edges that are already in weight order, which can then come lazily.
+    pub fn from_sorted(vertices: usize, edges: impl IntoIterator<IntoIter = I>) -> Self {
+        Self {
+            edges: edges.into_iter(),
+            components: DisjointSet::new(vertices),
+            largest: (vertices > 0) as usize,
+        }
+    }
+
+    /// Components formed by the edges seen so far.
+    pub fn components(&self) -> &DisjointSet {
+        &self.components
+    }
+}
+
+impl<W, I: Iterator<Item = Edge<W>>> Iterator for Kruskal<I> {
+    type Item = Event<W>;
+
+    fn next(&mut self) -> Option<Event<W>> {
+        let edge = self.edges.next()?;
+        let accepted = self.components.union(edge.a, edge.b);
+        if accepted {
+            self.largest = self.largest.max(self.components.size_of(edge.a));
+        }
+
+        Some(Event {
+            edge,
+            accepted,
+            components: self.components.component_count(),
+            largest: self.largest,
+        })
+    }
+}
+
+/// The edges of a minimum spanning forest, one tree per connected component.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpanningForest<W> {
+    pub edges: Vec<Edge<W>>,
+    pub components: usize,
+}
+
+impl<W: Copy + Sum> SpanningForest<W> {
+    pub fn total_weight(&self) -> W {
+        self.edges.iter().map(|e| e.weight).sum()
+    }
+}
+
+impl<W> SpanningForest<W> {
+    /// Whether the graph is connected, the forest being a single tree.
+    pub fn is_tree(&self) -> bool {
+        self.components <= 1
+    }
+}
+
+/// Minimum spanning forest of a graph with `vertices` vertices, stopping as soon as it is a
+/// single tree.
+pub fn minimum_spanning_forest<W: Copy + Ord>(
+    vertices: usize,
+    edges: impl IntoIterator<Item = Edge<W>>,
+) -> SpanningForest<W> {
+    let mut forest = SpanningForest {
+        edges: Vec::new(),
+        components: vertices,
+    };
+
+    for event in Kruskal::new(vertices, edges) {
+        if event.accepted {
+            forest.edges.push(event.edge);
+            forest.components = event.components;
+            if event.components == 1 {
+                break;
+            }
+        }
+    }
+
+    forest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edges() -> Vec<Edge<u32>> {
+        // Two triangles, 0 1 2 and 3 4 5, bridged by 2 - 3.
+        vec![
+            Edge::new(0, 1, 4),
+            Edge::new(1, 2, 2),
+            Edge::new(0, 2, 3),
+            Edge::new(3, 4, 1),
+            Edge::new(4, 5, 5),
+            Edge::new(3, 5, 5),
+            Edge::new(2, 3, 7),
+        ]
+    }
+
+    #[test]
+    fn test_events() {
+        let events: Vec<Event<u32>> = Kruskal::new(6, edges()).collect();
+
+        let order: Vec<(usize, usize, bool)> = events
+            .iter()
+            .map(|e| (e.edge.a, e.edge.b, e.accepted))
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                (3, 4, true),
+                (1, 2, true),
+                (0, 2, true),
+                (0, 1, false),
+                (4, 5, true),
+                (3, 5, false),
+                (2, 3, true),
+            ]
+        );
+
+        let components: Vec<usize> = events.iter().map(|e| e.components).collect();
+        assert_eq!(components, vec![5, 4, 3, 3, 2, 2, 1]);
+        let largest: Vec<usize> = events.iter().map(|e| e.largest).collect();
+        assert_eq!(largest, vec![2, 2, 3, 3, 3, 3, 6]);
+    }
+
+    #[test]
+    fn test_components_between_events() {
+        let mut kruskal = Kruskal::new(6, edges());
+        kruskal.by_ref().take(2).for_each(drop);
+        assert_eq!(kruskal.components().component_sizes(), vec![2, 2, 1, 1]);
+    }
+
+    #[test]
+    fn test_spanning_forest() {
+        let tree = minimum_spanning_forest(6, edges());
+        assert!(tree.is_tree());
+        assert_eq!(tree.edges.len(), 5);
+        assert_eq!(tree.total_weight(), 1 + 2 + 3 + 5 + 7);
+
+        let forest = minimum_spanning_forest(7, edges());
+        assert!(!forest.is_tree());
+        assert_eq!(forest.components, 2);
+        assert_eq!(forest.total_weight(), 18);
+    }
+}
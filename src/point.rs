@@ -0,0 +1,289 @@
+use std::fmt;
+use std::ops::{Add, Index, IndexMut, Sub};
+use std::str::FromStr;
+
+/// A point or vector with `N` coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point<T, const N: usize>(pub [T; N]);
+
+impl<T: Copy + Ord, const N: usize> Point<T, N> {
+    /// Component-wise minimum, the lowest corner of the box around both points.
+    pub fn min(self, other: Self) -> Self {
+        Self(std::array::from_fn(|i| self.0[i].min(other.0[i])))
+    }
+
+    /// Component-wise maximum, the highest corner of the box around both points.
+    pub fn max(self, other: Self) -> Self {
+        Self(std::array::from_fn(|i| self.0[i].max(other.0[i])))
+    }
+}
+
+/// Integer coordinates with overflow-checked arithmetic, see [`Point::checked_add`].
+pub trait CheckedCoord: Copy + Sized {
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+}
+
+macro_rules! impl_checked_coord {
+    ($($t:ty),*) => {
+        $(impl CheckedCoord for $t {
+            fn checked_add(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_add(self, rhs)
+            }
+
+            fn checked_sub(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_sub(self, rhs)
+            }
+        })*
+    };
+}
+
+impl_checked_coord!(
+    i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize
+);
+
+impl<T: CheckedCoord, const N: usize> Point<T, N> {
+    /// `self + rhs`, `None` if any coordinate overflows.
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        self.zip_checked(rhs, T::checked_add)
+    }
+
+    /// `self - rhs`, `None` if any coordinate overflows.
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        self.zip_checked(rhs, T::checked_sub)
+    }
+
+    fn zip_checked(self, rhs: Self, f: impl Fn(T, T) -> Option<T>) -> Option<Self> {
+        let mut out = self.0;
+        for (i, out) in out.iter_mut().enumerate() {
+            *out = f(self.0[i], rhs.0[i])?;
+        }
+        Some(Self(out))
+    }
+}
+
+// Distances go through `i128` and come out as `u128` so that no coordinate difference or square
+// overflows for coordinates up to 64 bits, and sums are checked for the remaining cases.
+//
+// `usize` and `isize` don't implement `Into<i128>` since their width depends on the target, so
+// grid positions have to be converted to a fixed-width type such as `i64` first.
+impl<T: Copy + Into<i128>, const N: usize> Point<T, N> {
+    fn abs_diffs(self, other: Self) -> impl Iterator<Item = u128> {
+        (0..N).map(move |i| self.0[i].into().abs_diff(other.0[i].into()))
+    }
+
+    /// Squared Euclidean distance, which compares like the distance itself without roots.
+    ///
+    /// Panics if it doesn't fit a `u128`, which takes coordinate differences close to 2^64.
+    pub fn dist_sqr(self, other: Self) -> u128 {
+        self.abs_diffs(other)
+            .try_fold(0u128, |sum, d| sum.checked_add(d.checked_mul(d)?))
+            .expect("squared distance overflows u128")
+    }
+
+    /// Sum of the coordinate differences, i.e. taxicab distance.
+    pub fn manhattan(self, other: Self) -> u128 {
+        self.abs_diffs(other)
+            .try_fold(0u128, |sum, d| sum.checked_add(d))
+            .expect("manhattan distance overflows u128")
+    }
+
+    /// Largest coordinate difference, i.e. king moves on a grid.
+    pub fn chebyshev(self, other: Self) -> u128 {
+        self.abs_diffs(other).max().unwrap_or(0)
+    }
+}
+
+/// How far apart two points are, all comparing like the real distance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Metric {
+    /// Straight-line distance, given squared to stay an integer.
+    #[default]
+    Euclidean,
+    Manhattan,
+    Chebyshev,
+}
+
+impl Metric {
+    pub fn distance<T: Copy + Into<i128>, const N: usize>(
+        self,
+        a: Point<T, N>,
+        b: Point<T, N>,
+    ) -> u128 {
+        match self {
+            Metric::Euclidean => a.dist_sqr(b),
+            Metric::Manhattan => a.manhattan(b),
+            Metric::Chebyshev => a.chebyshev(b),
+        }
+    }
+
+    /// Smallest distance possible between two points whose coordinates differ by `diff` on
+    /// some axis.
+    pub fn axis_bound(self, diff: u128) -> u128 {
+        match self {
+            Metric::Euclidean => diff * diff,
+            Metric::Manhattan | Metric::Chebyshev => diff,
+        }
+    }
+}
+
+/// Plain coordinate addition, which panics on overflow in debug builds and wraps in release
+/// ones like the underlying type. Use [`Point::checked_add`] when that can happen.
+impl<T: Copy + Add<Output = T>, const N: usize> Add for Point<T, N> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self(std::array::from_fn(|i| self.0[i] + rhs.0[i]))
+    }
+}
+
+/// Plain coordinate subtraction, see [`Point::checked_sub`].
+impl<T: Copy + Sub<Output = T>, const N: usize> Sub for Point<T, N> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self(std::array::from_fn(|i| self.0[i] - rhs.0[i]))
+    }
+}
+
+impl<T, const N: usize> Index<usize> for Point<T, N> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        &self.0[index]
+    }
+}
+
+impl<T, const N: usize> IndexMut<usize> for Point<T, N> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        &mut self.0[index]
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for Point<T, N> {
+    fn from(coords: [T; N]) -> Self {
+        Self(coords)
+    }
+}
+
+/// Why a line isn't a [`Point`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParsePointError<E> {
+    /// The line doesn't have exactly `N` comma-separated coordinates.
+    WrongLength { expected: usize, found: usize },
+    /// A coordinate didn't parse.
+    Coordinate(E),
+}
+
+impl<E: fmt::Display> fmt::Display for ParsePointError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParsePointError::WrongLength { expected, found } => {
+                write!(f, "expected {expected} coordinates, got {found}")
+            }
+            ParsePointError::Coordinate(e) => write!(f, "invalid coordinate: {e}"),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> std::error::Error for ParsePointError<E> {}
+
+/// Parses comma-separated coordinates like `162,817,812`, spaces around them being allowed.
+impl<T: FromStr, const N: usize> FromStr for Point<T, N> {
+    type Err = ParsePointError<T::Err>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let coords = s
+            .split(',')
+            .map(|w| w.trim().parse())
+            .collect::<Result<Vec<T>, _>>()
+            .map_err(ParsePointError::Coordinate)?;
+
+        let found = coords.len();
+        coords
+            .try_into()
+            .map(Self)
+            .map_err(|_| ParsePointError::WrongLength { expected: N, found })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arithmetic() {
+        let a = Point([1i64, -2, 3]);
+        let b = Point([4, 5, -6]);
+
+        assert_eq!(a + b, Point([5, 3, -3]));
+        assert_eq!(b - a, Point([3, 7, -9]));
+        assert_eq!(a.min(b), Point([1, -2, -6]));
+        assert_eq!(a.max(b), Point([4, 5, 3]));
+
+        assert_eq!(a.dist_sqr(b), 9 + 49 + 81);
+        assert_eq!(a.manhattan(b), 3 + 7 + 9);
+        assert_eq!(a.chebyshev(b), 9);
+        assert_eq!(a.dist_sqr(a), 0);
+
+        assert_eq!(Metric::Euclidean.distance(a, b), a.dist_sqr(b));
+        assert_eq!(Metric::Manhattan.distance(a, b), 19);
+        assert_eq!(Metric::Chebyshev.distance(a, b), 9);
+    }
+
+    #[test]
+    fn test_checked() {
+        let a = Point([1u8, 200]);
+        let b = Point([2u8, 60]);
+
+        assert_eq!(a.checked_add(Point([1, 55])), Some(Point([2, 255])));
+        assert_eq!(a.checked_add(b), None);
+        assert_eq!(b.checked_sub(Point([2, 0])), Some(Point([0, 60])));
+        assert_eq!(a.checked_sub(b), None);
+        assert_eq!(
+            Point([3usize, 4]).checked_sub(Point([3, 4])),
+            Some(Point([0, 0]))
+        );
+        assert_eq!(Point([i64::MIN]).checked_sub(Point([1])), None);
+    }
+
+    #[test]
+    fn test_large_coordinates() {
+        // The first difference squared overflows a u64, but the sum still fits a u128.
+        let a = Point([u64::MAX, u32::MAX as u64]);
+        let b = Point([0, 0]);
+        let d = u64::MAX as u128;
+        let e = u32::MAX as u128;
+        assert_eq!(a.dist_sqr(b), d * d + e * e);
+        assert_eq!(a.manhattan(b), d + e);
+
+        let c = Point([i64::MIN, i64::MIN]);
+        let d = Point([i64::MAX, i64::MAX]);
+        assert_eq!(c.chebyshev(d), u64::MAX as u128);
+    }
+
+    #[test]
+    #[should_panic(expected = "overflows")]
+    fn test_overflow_panics() {
+        let a = Point([0u64; 2]);
+        let b = Point([u64::MAX; 2]);
+        a.dist_sqr(b);
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("162,817,812".parse(), Ok(Point([162u32, 817, 812])));
+        assert_eq!(" -3, 4".parse(), Ok(Point([-3i64, 4])));
+        assert_eq!(
+            "1,2".parse::<Point<i64, 3>>(),
+            Err(ParsePointError::WrongLength {
+                expected: 3,
+                found: 2
+            })
+        );
+        assert!(matches!(
+            "1,x,3".parse::<Point<i64, 3>>(),
+            Err(ParsePointError::Coordinate(_))
+        ));
+    }
+}
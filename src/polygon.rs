@@ -0,0 +1,559 @@
+use std::fmt;
+
+use crate::IntervalSet;
+use crate::compress::CoordCompressor;
+use crate::grid::Grid;
+use crate::prefix_sum::PrefixSum2D;
+
+/// A polygon vertex, `[x, y]` with `y` pointing down like in the puzzle inputs.
+pub type Vertex = [i64; 2];
+
+/// Reasons why a list of vertices is not a valid [`RectilinearPolygon`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolygonError {
+    /// A rectilinear polygon needs at least 4 vertices.
+    TooFewVertices(usize),
+    /// The edge from vertex `index` to the next one is neither horizontal nor vertical.
+    NotAxisAligned { index: usize },
+    /// Vertex `index` and the next one are the same point.
+    ZeroLengthEdge { index: usize },
+    /// The edges starting at vertices `a` and `b` cross, touch or overlap.
+    SelfIntersecting { a: usize, b: usize },
+}
+
+impl fmt::Display for PolygonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolygonError::TooFewVertices(n) => {
+                write!(f, "expected at least 4 vertices, got {n}")
+            }
+            PolygonError::NotAxisAligned { index } => {
+                write!(f, "edge {index} is neither horizontal nor vertical")
+            }
+            PolygonError::ZeroLengthEdge { index } => write!(f, "edge {index} has no length"),
+            PolygonError::SelfIntersecting { a, b } => {
+                write!(f, "edges {a} and {b} intersect")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PolygonError {}
+
+/// Direction in which the vertices go around the polygon, as seen on screen with `y` pointing
+/// down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    Clockwise,
+    CounterClockwise,
+}
+
+/// A simple polygon whose edges are all horizontal or vertical.
+///
+/// The loop is closed implicitly, from the last vertex back to the first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RectilinearPolygon {
+    vertices: Vec<Vertex>,
+}
+
+impl RectilinearPolygon {
+    /// Validates the vertices, a repeated first vertex at the end being accepted and dropped.
+    pub fn new(mut vertices: Vec<Vertex>) -> Result<Self, PolygonError> {
+        if vertices.len() > 1 && vertices.first() == vertices.last() {
+            vertices.pop();
+        }
+
+        if vertices.len() < 4 {
+            return Err(PolygonError::TooFewVertices(vertices.len()));
+        }
+
+        let polygon = Self { vertices };
+        let edges: Vec<(Vertex, Vertex)> = polygon.edges().collect();
+
+        for (index, &(a, b)) in edges.iter().enumerate() {
+            match (a[0] == b[0], a[1] == b[1]) {
+                (true, true) => return Err(PolygonError::ZeroLengthEdge { index }),
+                (false, false) => return Err(PolygonError::NotAxisAligned { index }),
+                _ => {}
+            }
+        }
+
+        let n = edges.len();
+        for i in 0..n {
+            for j in i + 1..n {
+                let adjacent = j == i + 1 || (i == 0 && j == n - 1);
+                let (e, f) = if i == 0 && j == n - 1 {
+                    (edges[j], edges[i])
+                } else {
+                    (edges[i], edges[j])
+                };
+
+                let intersects = if adjacent {
+                    // They share a vertex, which is fine unless `f` goes back over `e`.
+                    let (d0, d1) = (direction(e), direction(f));
+                    d0[0] == -d1[0] && d0[1] == -d1[1]
+                } else {
+                    boxes_overlap(e, f)
+                };
+
+                if intersects {
+                    return Err(PolygonError::SelfIntersecting { a: i, b: j });
+                }
+            }
+        }
+
+        Ok(polygon)
+    }
+
+    pub fn vertices(&self) -> &[Vertex] {
+        &self.vertices
+    }
+
+    /// Every edge, including the one closing the loop.
+    pub fn edges(&self) -> impl Iterator<Item = (Vertex, Vertex)> + '_ {
+        let n = self.vertices.len();
+        (0..n).map(move |i| (self.vertices[i], self.vertices[(i + 1) % n]))
+    }
+
+    /// Twice the signed area, positive when clockwise on screen.
+    fn double_signed_area(&self) -> i64 {
+        self.edges()
+            .map(|([x0, y0], [x1, y1])| x0 * y1 - x1 * y0)
+            .sum()
+    }
+
+    pub fn orientation(&self) -> Orientation {
+        if self.double_signed_area() > 0 {
+            Orientation::Clockwise
+        } else {
+            Orientation::CounterClockwise
+        }
+    }
+
+    /// Geometric area enclosed by the edges, vertices being points.
+    pub fn area(&self) -> i64 {
+        self.double_signed_area().abs() / 2
+    }
+
+    pub fn boundary_length(&self) -> i64 {
+        self.edges()
+            .map(|(a, b)| (a[0] - b[0]).abs() + (a[1] - b[1]).abs())
+            .sum()
+    }
+
+    /// Number of integer points inside the polygon or on its boundary, i.e. the number of tiles
+    /// covered when every vertex is a tile (Pick's theorem).
+    pub fn tile_count(&self) -> i64 {
+        self.area() + self.boundary_length() / 2 + 1
+    }
+
+    pub fn on_boundary(&self, p: Vertex) -> bool {
+        self.edges().any(|e| boxes_overlap(e, (p, p)))
+    }
+
+    /// Whether `p` is inside the polygon or on its boundary.
+    pub fn contains(&self, p: Vertex) -> bool {
+        if self.on_boundary(p) {
+            return true;
+        }
+
+        // Cast a ray towards +x and count the vertical edges it crosses. Counting an edge only
+        // when `y0 <= y < y1` handles rays going exactly through vertices.
+        let crossings = self
+            .edges()
+            .filter(|&(a, b)| {
+                let (y0, y1) = (a[1].min(b[1]), a[1].max(b[1]));
+                a[0] == b[0] && a[0] > p[0] && y0 <= p[1] && p[1] < y1
+            })
+            .count();
+
+        crossings % 2 == 1
+    }
+
+    /// Rasterizes the polygon on its compressed grid, see [`Raster`].
+    pub fn raster(&self) -> Raster {
+        let columns = spans(self.vertices.iter().map(|v| v[0]).collect());
+        let rows = spans(self.vertices.iter().map(|v| v[1]).collect());
+
+        let mut inside = Grid::new(columns.len(), rows.len(), false);
+        for (r, &(y, _)) in rows.iter().enumerate() {
+            // Same half-open rule as `contains`: between two crossings is inside, and what the
+            // pairs miss on this line is on a horizontal edge.
+            let mut crossings: Vec<i64> = self
+                .edges()
+                .filter(|&(a, b)| a[0] == b[0] && a[1].min(b[1]) <= y && y < a[1].max(b[1]))
+                .map(|(a, _)| a[0])
+                .collect();
+            crossings.sort_unstable();
+
+            let mut covered = IntervalSet::new();
+            for pair in crossings.chunks(2) {
+                covered.insert(pair[0]..pair[1] + 1);
+            }
+            for (a, b) in self.edges().filter(|(a, b)| a[1] == y && b[1] == y) {
+                covered.insert(a[0].min(b[0])..a[0].max(b[0]) + 1);
+            }
+
+            for (c, &(x, _)) in columns.iter().enumerate() {
+                inside[(c, r)] = covered.contains(&x);
+            }
+        }
+
+        Raster {
+            columns,
+            rows,
+            inside,
+        }
+    }
+
+    /// The rectangle covering the most tiles that fit in the polygon, boundary included.
+    ///
+    /// A largest rectangle can always be grown until every side touches the outside, so it is
+    /// made of whole raster cells. Each raster row is the base of a histogram of how far up
+    /// every column stays inside, and the largest rectangle under a histogram comes from a
+    /// single pass with a stack.
+    pub fn largest_rectangle(&self) -> Rect {
+        let raster = self.raster();
+        let (width, height) = (raster.inside.width(), raster.inside.height());
+
+        // Tiles from the top of the run of inside cells ending on the current row, and the
+        // raster row where that run starts.
+        let mut runs = vec![(0, 0); width];
+        let mut best: Option<Rect> = None;
+
+        for r in 0..height {
+            let (y0, y1) = raster.rows[r];
+            for (c, run) in runs.iter_mut().enumerate() {
+                *run = match raster.inside[(c, r)] {
+                    true if run.0 > 0 => (run.0 + y1 - y0 + 1, run.1),
+                    true => (y1 - y0 + 1, r),
+                    false => (0, r),
+                };
+            }
+
+            // Columns whose run is the lowest from where they are pushed to the current one,
+            // with the first column that run spans.
+            let mut stack: Vec<(usize, usize)> = Vec::new();
+            for c in 0..=width {
+                let tiles = runs.get(c).map_or(0, |run| run.0);
+                let mut first = c;
+
+                while let Some(&(top, from)) = stack.last() {
+                    if runs[top].0 < tiles {
+                        break;
+                    }
+                    stack.pop();
+                    first = from;
+
+                    let rect = Rect {
+                        min: [raster.columns[from].0, raster.rows[runs[top].1].0],
+                        max: [raster.columns[c - 1].1, y1],
+                    };
+                    if runs[top].0 > 0
+                        && best.is_none_or(|best| rect.tile_count() > best.tile_count())
+                    {
+                        best = Some(rect);
+                    }
+                }
+
+                if c < width {
+                    stack.push((c, first));
+                }
+            }
+        }
+
+        // Every vertex is on the boundary, so there is always at least one inside cell.
+        best.unwrap()
+    }
+
+    /// The rectangle covering the most tiles that fits in the polygon with two of the
+    /// `candidates` as opposite corners, `None` if no pair of candidates makes one.
+    pub fn largest_rectangle_with_corners(&self, candidates: &[Vertex]) -> Option<Rect> {
+        let raster = self.raster();
+        let outside = PrefixSum2D::new(&raster.inside, |&inside| !inside as usize);
+        let cells: Vec<Option<(usize, usize)>> = candidates
+            .iter()
+            .map(|p| raster.column_of(p[0]).zip(raster.row_of(p[1])))
+            .collect();
+
+        let mut best: Option<Rect> = None;
+        for (i, a) in cells.iter().enumerate() {
+            for (j, b) in cells.iter().enumerate().skip(i + 1) {
+                let (Some(a), Some(b)) = (a, b) else {
+                    continue;
+                };
+
+                let xs = a.0.min(b.0)..a.0.max(b.0) + 1;
+                let ys = a.1.min(b.1)..a.1.max(b.1) + 1;
+                if outside.sum(xs, ys) > 0 {
+                    continue;
+                }
+
+                let rect = Rect::from_corners(candidates[i], candidates[j]);
+                if best.is_none_or(|best| rect.tile_count() > best.tile_count()) {
+                    best = Some(rect);
+                }
+            }
+        }
+
+        best
+    }
+}
+
+/// An axis-aligned rectangle of tiles, both corners included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rect {
+    pub min: Vertex,
+    pub max: Vertex,
+}
+
+impl Rect {
+    /// The rectangle with two opposite corners `a` and `b`.
+    pub fn from_corners(a: Vertex, b: Vertex) -> Self {
+        Self {
+            min: [a[0].min(b[0]), a[1].min(b[1])],
+            max: [a[0].max(b[0]), a[1].max(b[1])],
+        }
+    }
+
+    /// Number of tiles covered.
+    pub fn tile_count(&self) -> i64 {
+        (self.max[0] - self.min[0] + 1) * (self.max[1] - self.min[1] + 1)
+    }
+}
+
+/// A polygon on a compressed grid whose cells are either a single vertex coordinate or the whole
+/// gap between two consecutive ones, so that every tile of a cell is on the same side.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Raster {
+    /// Inclusive `(first, last)` x of the tiles in every column.
+    pub columns: Vec<(i64, i64)>,
+    /// Inclusive `(first, last)` y of the tiles in every row.
+    pub rows: Vec<(i64, i64)>,
+    /// Whether the tiles of a cell are inside the polygon or on its boundary.
+    pub inside: Grid<bool>,
+}
+
+impl Raster {
+    /// Column holding the tiles at `x`, `None` outside of the polygon's bounds.
+    pub fn column_of(&self, x: i64) -> Option<usize> {
+        span_of(&self.columns, x)
+    }
+
+    /// Row holding the tiles at `y`, `None` outside of the polygon's bounds.
+    pub fn row_of(&self, y: i64) -> Option<usize> {
+        span_of(&self.rows, y)
+    }
+}
+
+/// Every distinct value as its own span, with the gaps between them in between.
+fn spans(values: Vec<i64>) -> Vec<(i64, i64)> {
+    let values = CoordCompressor::new(values);
+    let mut spans = Vec::new();
+    for (i, &v) in values.values().iter().enumerate() {
+        if i > 0 && values.values()[i - 1] + 1 < v {
+            spans.push((values.values()[i - 1] + 1, v - 1));
+        }
+        spans.push((v, v));
+    }
+    spans
+}
+
+fn span_of(spans: &[(i64, i64)], v: i64) -> Option<usize> {
+    let i = spans.partition_point(|&(_, last)| last < v);
+    (i < spans.len() && spans[i].0 <= v).then_some(i)
+}
+
+/// Unit step along an axis-aligned edge.
+fn direction((a, b): (Vertex, Vertex)) -> [i64; 2] {
+    [(b[0] - a[0]).signum(), (b[1] - a[1]).signum()]
+}
+
+/// Axis-aligned segments are their own bounding box, so they meet iff their boxes overlap.
+fn boxes_overlap((a, b): (Vertex, Vertex), (c, d): (Vertex, Vertex)) -> bool {
+    (0..2).all(|axis| {
+        let lo = a[axis].min(b[axis]).max(c[axis].min(d[axis]));
+        let hi = a[axis].max(b[axis]).min(c[axis].max(d[axis]));
+        lo <= hi
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day09_example() -> Vec<Vertex> {
+        vec![
+            [7, 1],
+            [11, 1],
+            [11, 7],
+            [9, 7],
+            [9, 5],
+            [2, 5],
+            [2, 3],
+            [7, 3],
+        ]
+    }
+
+    #[test]
+    fn test_measures() {
+        let polygon = RectilinearPolygon::new(day09_example()).unwrap();
+
+        assert_eq!(polygon.orientation(), Orientation::Clockwise);
+        assert_eq!(polygon.area(), 30);
+        assert_eq!(polygon.boundary_length(), 30);
+        assert_eq!(polygon.tile_count(), 46);
+
+        let mut reversed = day09_example();
+        reversed.reverse();
+        let reversed = RectilinearPolygon::new(reversed).unwrap();
+        assert_eq!(reversed.orientation(), Orientation::CounterClockwise);
+        assert_eq!(reversed.area(), 30);
+    }
+
+    #[test]
+    fn test_contains_any_orientation_and_start() {
+        let polygon = RectilinearPolygon::new(day09_example()).unwrap();
+
+        // Go the other way around, with a horizontal loopback segment this time.
+        let mut rotated = day09_example();
+        rotated.rotate_left(1);
+        rotated.reverse();
+        let rotated = RectilinearPolygon::new(rotated).unwrap();
+
+        let mut tiles = 0;
+        for y in 0..10 {
+            for x in 0..14 {
+                let inside = polygon.contains([x, y]);
+                assert_eq!(inside, rotated.contains([x, y]), "{x},{y}");
+                tiles += inside as i64;
+            }
+        }
+        assert_eq!(tiles, polygon.tile_count());
+
+        assert!(polygon.contains([7, 1]));
+        assert!(polygon.contains([10, 6]));
+        assert!(polygon.contains([2, 4]));
+        assert!(!polygon.contains([3, 2]));
+        assert!(!polygon.contains([10, 8]));
+        assert!(!polygon.contains([8, 6]));
+    }
+
+    #[test]
+    fn test_raster() {
+        let polygon = RectilinearPolygon::new(day09_example()).unwrap();
+        let raster = polygon.raster();
+
+        // 2, 3..=6, 7, 8, 9, 10, 11 and 1, 2, 3, 4, 5, 6, 7.
+        assert_eq!(raster.columns.len(), 7);
+        assert_eq!(raster.rows.len(), 7);
+        assert_eq!(raster.columns[1], (3, 6));
+        assert_eq!(raster.column_of(5), Some(1));
+        assert_eq!(raster.column_of(1), None);
+        assert_eq!(raster.row_of(8), None);
+
+        for y in 0..10 {
+            for x in 0..14 {
+                let cell = raster.column_of(x).zip(raster.row_of(y));
+                let inside = cell.is_some_and(|c| raster.inside[c]);
+                assert_eq!(inside, polygon.contains([x, y]), "{x},{y}");
+            }
+        }
+    }
+
+    /// Largest rectangle by trying every rectangle in `0..size` squared.
+    fn brute_force_largest(polygon: &RectilinearPolygon, size: i64) -> i64 {
+        let mut best = 0;
+        for x0 in 0..size {
+            for y0 in 0..size {
+                for x1 in x0..size {
+                    for y1 in y0..size {
+                        let fits = (x0..=x1).all(|x| (y0..=y1).all(|y| polygon.contains([x, y])));
+                        if fits {
+                            best = best.max((x1 - x0 + 1) * (y1 - y0 + 1));
+                        }
+                    }
+                }
+            }
+        }
+        best
+    }
+
+    #[test]
+    fn test_largest_rectangle() {
+        let polygon = RectilinearPolygon::new(day09_example()).unwrap();
+
+        let any = polygon.largest_rectangle();
+        assert_eq!(any, Rect::from_corners([2, 3], [11, 5]));
+        assert_eq!(any.tile_count(), brute_force_largest(&polygon, 13));
+
+        let anchored = polygon
+            .largest_rectangle_with_corners(polygon.vertices())
+            .unwrap();
+        assert_eq!(anchored, Rect::from_corners([9, 5], [2, 3]));
+        assert_eq!(anchored.tile_count(), 24);
+
+        assert_eq!(
+            polygon.largest_rectangle_with_corners(&[[7, 1], [0, 0]]),
+            None
+        );
+
+        // A staircase with a notch in its top edge.
+        let stairs = RectilinearPolygon::new(vec![
+            [0, 0],
+            [3, 0],
+            [3, 2],
+            [4, 2],
+            [4, 0],
+            [6, 0],
+            [6, 4],
+            [9, 4],
+            [9, 7],
+            [1, 7],
+            [1, 5],
+            [0, 5],
+        ])
+        .unwrap();
+        assert_eq!(
+            stairs.largest_rectangle().tile_count(),
+            brute_force_largest(&stairs, 11)
+        );
+    }
+
+    #[test]
+    fn test_closing_vertex() {
+        let mut closed = day09_example();
+        closed.push(closed[0]);
+
+        assert_eq!(
+            RectilinearPolygon::new(closed),
+            RectilinearPolygon::new(day09_example())
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            RectilinearPolygon::new(vec![[0, 0], [1, 0], [1, 1]]),
+            Err(PolygonError::TooFewVertices(3))
+        );
+        assert_eq!(
+            RectilinearPolygon::new(vec![[0, 0], [2, 0], [2, 2], [1, 3], [0, 2]]),
+            Err(PolygonError::NotAxisAligned { index: 2 })
+        );
+        assert_eq!(
+            RectilinearPolygon::new(vec![[0, 0], [2, 0], [2, 0], [2, 2], [0, 2]]),
+            Err(PolygonError::ZeroLengthEdge { index: 1 })
+        );
+        // A figure eight whose edges cross at (2, 2).
+        assert_eq!(
+            RectilinearPolygon::new(vec![[0, 0], [2, 0], [2, 4], [4, 4], [4, 2], [0, 2]]),
+            Err(PolygonError::SelfIntersecting { a: 1, b: 4 })
+        );
+        // Going back over the previous edge.
+        assert_eq!(
+            RectilinearPolygon::new(vec![[0, 0], [3, 0], [1, 0], [1, 2], [0, 2]]),
+            Err(PolygonError::SelfIntersecting { a: 0, b: 1 })
+        );
+    }
+}
@@ -0,0 +1,86 @@
+use std::ops::{Add, Range, Sub};
+
+use crate::grid::Grid;
+
+/// A summed-area table: the sum of any rectangle of a grid in O(1).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrefixSum2D<T> {
+    // `sums[(x, y)]` is the sum of every cell above and to the left of `(x, y)`, excluded, so
+    // the first row and column are all zeroes.
+    sums: Grid<T>,
+}
+
+impl<T: Copy + Default + Add<Output = T> + Sub<Output = T>> PrefixSum2D<T> {
+    /// Builds the table of the values `f` gives to every cell of `grid`.
+    pub fn new<U>(grid: &Grid<U>, f: impl Fn(&U) -> T) -> Self {
+        let mut sums = Grid::new(grid.width() + 1, grid.height() + 1, T::default());
+
+        for ((x, y), cell) in grid.iter() {
+            sums[(x + 1, y + 1)] = f(cell) + sums[(x, y + 1)] + sums[(x + 1, y)] - sums[(x, y)];
+        }
+
+        Self { sums }
+    }
+
+    pub fn width(&self) -> usize {
+        self.sums.width() - 1
+    }
+
+    pub fn height(&self) -> usize {
+        self.sums.height() - 1
+    }
+
+    /// Sum of the cells in `x` by `y`.
+    pub fn sum(&self, x: Range<usize>, y: Range<usize>) -> T {
+        assert!(x.start <= x.end && x.end <= self.width());
+        assert!(y.start <= y.end && y.end <= self.height());
+
+        self.sums[(x.end, y.end)] + self.sums[(x.start, y.start)]
+            - self.sums[(x.start, y.end)]
+            - self.sums[(x.end, y.start)]
+    }
+
+    pub fn total(&self) -> T {
+        self.sum(0..self.width(), 0..self.height())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_matches_naive_sums() {
+        let mut rng = Rng::new(39);
+        let grid = Grid::from_fn(7, 5, |_, _| rng.range(0..100) as i64 - 50);
+        let table = PrefixSum2D::new(&grid, |&v| v);
+
+        for x0 in 0..=7 {
+            for x1 in x0..=7 {
+                for y0 in 0..=5 {
+                    for y1 in y0..=5 {
+                        let expected: i64 = grid
+                            .iter()
+                            .filter(|((x, y), _)| (x0..x1).contains(x) && (y0..y1).contains(y))
+                            .map(|(_, v)| v)
+                            .sum();
+                        assert_eq!(table.sum(x0..x1, y0..y1), expected);
+                    }
+                }
+            }
+        }
+
+        assert_eq!(table.total(), grid.iter().map(|(_, v)| v).sum::<i64>());
+    }
+
+    #[test]
+    fn test_counts() {
+        let grid = Grid::parse("#.#\n.##\n", |b| b == b'#');
+        let table = PrefixSum2D::new(&grid, |&set| set as usize);
+
+        assert_eq!(table.total(), 4);
+        assert_eq!(table.sum(1..3, 0..2), 3);
+        assert_eq!(table.sum(1..1, 0..2), 0);
+    }
+}
@@ -0,0 +1,186 @@
+//! Dumps grids as coloured terminal text or PPM images, to look at what a puzzle is doing.
+//!
+//! PPM is about the simplest image format there is: a tiny text header followed by raw RGB
+//! bytes. Most image viewers open it, and e.g. `ffmpeg -i frame_%05d.ppm out.gif` turns a
+//! [`FrameWriter`] sequence into an animation.
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::grid::Grid;
+
+pub type Rgb = [u8; 3];
+
+/// Renders a grid as text, `f` giving the character of every cell and optionally its colour.
+///
+/// Colours use 24-bit ANSI escape codes, which most terminals support.
+pub fn ansi<T>(grid: &Grid<T>, f: impl Fn(&T) -> (char, Option<Rgb>)) -> String {
+    let mut out = String::new();
+    for row in grid.rows() {
+        let mut coloured = false;
+        for cell in row {
+            match f(cell) {
+                (c, Some([r, g, b])) => {
+                    out.push_str(&format!("\x1b[38;2;{r};{g};{b}m{c}"));
+                    coloured = true;
+                }
+                (c, None) => {
+                    if coloured {
+                        out.push_str("\x1b[0m");
+                        coloured = false;
+                    }
+                    out.push(c);
+                }
+            }
+        }
+        if coloured {
+            out.push_str("\x1b[0m");
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Writes a grid as a binary PPM image, every cell being a `scale` x `scale` square.
+pub fn write_ppm<T>(
+    w: &mut impl Write,
+    grid: &Grid<T>,
+    scale: usize,
+    f: impl Fn(&T) -> Rgb,
+) -> io::Result<()> {
+    assert!(scale > 0);
+
+    writeln!(
+        w,
+        "P6\n{} {}\n255",
+        grid.width() * scale,
+        grid.height() * scale
+    )?;
+
+    let mut line = Vec::with_capacity(grid.width() * scale * 3);
+    for row in grid.rows() {
+        line.clear();
+        for cell in row {
+            let rgb = f(cell);
+            for _ in 0..scale {
+                line.extend_from_slice(&rgb);
+            }
+        }
+        for _ in 0..scale {
+            w.write_all(&line)?;
+        }
+    }
+    Ok(())
+}
+
+/// Maps `value` in `0..=max` to a black, red, yellow, white gradient, for counts and distances.
+pub fn heat(value: usize, max: usize) -> Rgb {
+    if max == 0 {
+        return [0, 0, 0];
+    }
+
+    // Spread the gradient over 3 * 255 steps, one colour channel at a time.
+    let t = value.min(max) * 765 / max;
+    let channel = |from: usize| (t.saturating_sub(from)).min(255) as u8;
+    [channel(0), channel(255), channel(510)]
+}
+
+/// Writes numbered PPM frames into a directory, e.g. one per generation of an automaton.
+pub struct FrameWriter {
+    dir: PathBuf,
+    prefix: String,
+    scale: usize,
+    next: usize,
+}
+
+impl FrameWriter {
+    /// Creates `dir` if needed, frames are named `{prefix}_00000.ppm`, `{prefix}_00001.ppm`, ...
+    pub fn new(dir: impl AsRef<Path>, prefix: &str, scale: usize) -> io::Result<Self> {
+        fs::create_dir_all(dir.as_ref())?;
+        Ok(Self {
+            dir: dir.as_ref().to_path_buf(),
+            prefix: prefix.to_string(),
+            scale,
+            next: 0,
+        })
+    }
+
+    /// Writes the next frame, returning its path.
+    pub fn write<T>(&mut self, grid: &Grid<T>, f: impl Fn(&T) -> Rgb) -> io::Result<PathBuf> {
+        let path = self
+            .dir
+            .join(format!("{}_{:05}.ppm", self.prefix, self.next));
+        let mut file = BufWriter::new(File::create(&path)?);
+        write_ppm(&mut file, grid, self.scale, f)?;
+        file.flush()?;
+
+        self.next += 1;
+        Ok(path)
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.next
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ansi() {
+        let grid = Grid::parse("#.\n", |b| b);
+        let text = ansi(&grid, |&b| match b {
+            b'#' => ('#', Some([255, 0, 0])),
+            _ => ('.', None),
+        });
+
+        assert_eq!(text, "\x1b[38;2;255;0;0m#\x1b[0m.\n");
+    }
+
+    #[test]
+    fn test_ppm() {
+        let grid = Grid::parse("#.\n", |b| b == b'#');
+        let mut out = Vec::new();
+        write_ppm(&mut out, &grid, 2, |&on| {
+            if on { [255, 255, 255] } else { [0, 0, 0] }
+        })
+        .unwrap();
+
+        let header = b"P6\n4 2\n255\n";
+        assert_eq!(&out[..header.len()], header);
+
+        let row = [[255u8; 6], [0; 6]].concat();
+        assert_eq!(&out[header.len()..], [row.clone(), row].concat());
+    }
+
+    #[test]
+    fn test_heat() {
+        assert_eq!(heat(0, 10), [0, 0, 0]);
+        assert_eq!(heat(10, 10), [255, 255, 255]);
+        assert_eq!(heat(20, 10), [255, 255, 255]);
+        assert_eq!(heat(5, 10)[0], 255);
+        assert_eq!(heat(0, 0), [0, 0, 0]);
+    }
+
+    #[test]
+    fn test_frame_writer() {
+        let dir = std::env::temp_dir().join(format!("aoc2025-frames-{}", std::process::id()));
+        let mut frames = FrameWriter::new(&dir, "test", 1).unwrap();
+
+        let grid = Grid::new(2, 2, [1, 2, 3]);
+        let first = frames.write(&grid, |&rgb| rgb).unwrap();
+        let second = frames.write(&grid, |&rgb| rgb).unwrap();
+
+        assert_eq!(frames.frame_count(), 2);
+        assert!(first.ends_with("test_00000.ppm"));
+        assert!(second.ends_with("test_00001.ppm"));
+        assert_eq!(
+            fs::read(&second).unwrap().len(),
+            b"P6\n2 2\n255\n".len() + 12
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
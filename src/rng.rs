@@ -0,0 +1,73 @@
+use std::ops::Range;
+
+/// Small deterministic pseudo-random number generator (SplitMix64).
+///
+/// Good enough for randomized tests and shuffling inputs, not for anything that needs
+/// cryptographic quality.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number uniformly distributed in `range`.
+    pub fn range(&mut self, range: Range<usize>) -> usize {
+        assert!(range.start < range.end);
+
+        let len = (range.end - range.start) as u64;
+        // Multiply-shift keeps the bias negligible for the small ranges we use.
+        range.start + ((self.next_u64() as u128 * len as u128) >> 64) as usize
+    }
+
+    /// Returns `true` with probability `1 / n`.
+    pub fn one_in(&mut self, n: usize) -> bool {
+        self.range(0..n) == 0
+    }
+
+    /// Fisher-Yates shuffle.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.range(0..i + 1));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deterministic_and_in_range() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+
+        for _ in 0..1000 {
+            let x = a.range(3..10);
+            assert_eq!(x, b.range(3..10));
+            assert!((3..10).contains(&x));
+        }
+    }
+
+    #[test]
+    fn test_shuffle_is_permutation() {
+        let mut rng = Rng::new(7);
+        let mut items: Vec<usize> = (0..50).collect();
+        rng.shuffle(&mut items);
+
+        assert_ne!(items, (0..50).collect::<Vec<_>>());
+        items.sort();
+        assert_eq!(items, (0..50).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,238 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+
+use crate::grid::Grid;
+
+/// Distances from a start cell, with the previous cell on a shortest path to every cell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShortestPaths {
+    pub start: (usize, usize),
+    /// `None` for cells that can't be reached.
+    pub dist: Grid<Option<usize>>,
+    pub prev: Grid<Option<(usize, usize)>>,
+}
+
+impl ShortestPaths {
+    fn new<T>(grid: &Grid<T>, start: (usize, usize)) -> Self {
+        let mut dist = Grid::new(grid.width(), grid.height(), None);
+        dist[start] = Some(0);
+        Self {
+            start,
+            dist,
+            prev: Grid::new(grid.width(), grid.height(), None),
+        }
+    }
+
+    /// Cells of a shortest path from the start to `target`, both included.
+    pub fn path_to(&self, target: (usize, usize)) -> Option<Vec<(usize, usize)>> {
+        self.dist.get(target.0, target.1).copied().flatten()?;
+
+        let mut path = vec![target];
+        let mut pos = target;
+        while let Some(prev) = self.prev[pos] {
+            path.push(prev);
+            pos = prev;
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// Breadth-first search from `start`, moving along `stencil` into cells that are `passable`.
+///
+/// The start cell itself doesn't need to be passable.
+pub fn bfs<T>(
+    grid: &Grid<T>,
+    start: (usize, usize),
+    stencil: &[(isize, isize)],
+    passable: impl Fn(&T) -> bool,
+) -> ShortestPaths {
+    let mut paths = ShortestPaths::new(grid, start);
+    let mut queue = VecDeque::from([(start, 0)]);
+
+    while let Some((pos, d)) = queue.pop_front() {
+        for next in grid.neighbors(pos, stencil) {
+            if paths.dist[next].is_none() && passable(&grid[next]) {
+                paths.dist[next] = Some(d + 1);
+                paths.prev[next] = Some(pos);
+                queue.push_back((next, d + 1));
+            }
+        }
+    }
+
+    paths
+}
+
+/// Dijkstra from `start`, `cost` giving the cost of stepping into a cell, `None` for walls.
+pub fn dijkstra<T>(
+    grid: &Grid<T>,
+    start: (usize, usize),
+    stencil: &[(isize, isize)],
+    cost: impl Fn(&T) -> Option<usize>,
+) -> ShortestPaths {
+    let mut paths = ShortestPaths::new(grid, start);
+    let mut queue = BinaryHeap::from([Reverse((0, start))]);
+
+    while let Some(Reverse((d, pos))) = queue.pop() {
+        if paths.dist[pos].is_some_and(|best| best < d) {
+            // Already reached through a cheaper path.
+            continue;
+        }
+
+        for next in grid.neighbors(pos, stencil) {
+            let Some(c) = cost(&grid[next]) else {
+                continue;
+            };
+
+            let next_d = d + c;
+            if paths.dist[next].is_none_or(|best| next_d < best) && next != start {
+                paths.dist[next] = Some(next_d);
+                paths.prev[next] = Some(pos);
+                queue.push(Reverse((next_d, next)));
+            }
+        }
+    }
+
+    paths
+}
+
+/// Cells reachable from `start` through `passable` cells, the start cell included.
+pub fn flood_fill<T>(
+    grid: &Grid<T>,
+    start: (usize, usize),
+    stencil: &[(isize, isize)],
+    passable: impl Fn(&T) -> bool,
+) -> Grid<bool> {
+    bfs(grid, start, stencil, passable)
+        .dist
+        .map(|d| d.is_some())
+}
+
+/// Result of [`label_components`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Components {
+    /// Component of every cell, numbered in the order they are first met row by row.
+    pub labels: Grid<usize>,
+    pub sizes: Vec<usize>,
+}
+
+impl Components {
+    pub fn count(&self) -> usize {
+        self.sizes.len()
+    }
+}
+
+/// Splits the grid into connected components, two neighbouring cells along `stencil` being
+/// in the same component when `connected` says so, e.g. `|a, b| a == b` for regions.
+///
+/// Use `NEIGHBORS4` or `NEIGHBORS8` for 4 or 8 connectivity, `connected` should be symmetric.
+pub fn label_components<T>(
+    grid: &Grid<T>,
+    stencil: &[(isize, isize)],
+    connected: impl Fn(&T, &T) -> bool,
+) -> Components {
+    let mut labels = Grid::new(grid.width(), grid.height(), None);
+    let mut sizes = Vec::new();
+    let mut stack = Vec::new();
+
+    for start in grid.positions() {
+        if labels[start].is_some() {
+            continue;
+        }
+
+        let label = sizes.len();
+        let mut size = 0;
+        labels[start] = Some(label);
+        stack.push(start);
+
+        while let Some(pos) = stack.pop() {
+            size += 1;
+            for next in grid.neighbors(pos, stencil) {
+                if labels[next].is_none() && connected(&grid[pos], &grid[next]) {
+                    labels[next] = Some(label);
+                    stack.push(next);
+                }
+            }
+        }
+
+        sizes.push(size);
+    }
+
+    Components {
+        labels: labels.map(|l| l.unwrap()),
+        sizes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::{NEIGHBORS4, NEIGHBORS8};
+
+    const MAZE: &str = "\
+S.#.
+.##.
+....
+#.#E
+";
+
+    #[test]
+    fn test_bfs_path() {
+        let grid = Grid::parse(MAZE, |b| b);
+        let paths = bfs(&grid, (0, 0), &NEIGHBORS4, |&c| c != b'#');
+
+        assert_eq!(paths.dist[(3, 3)], Some(6));
+        assert_eq!(paths.dist[(2, 0)], None);
+        assert_eq!(paths.dist[(3, 0)], Some(7));
+
+        let path = paths.path_to((3, 3)).unwrap();
+        assert_eq!(path.len(), 7);
+        assert_eq!(path[0], (0, 0));
+        assert_eq!(path[6], (3, 3));
+        assert!(
+            path.windows(2)
+                .all(|w| w[0].0.abs_diff(w[1].0) + w[0].1.abs_diff(w[1].1) == 1)
+        );
+        assert_eq!(paths.path_to((2, 0)), None);
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        let grid = Grid::parse("1191\n1911\n1111\n", |b| (b - b'0') as usize);
+        let paths = dijkstra(&grid, (0, 0), &NEIGHBORS4, |&c| Some(c));
+
+        // Going around the 9s is cheaper than through them.
+        assert_eq!(paths.dist[(3, 0)], Some(7));
+        assert_eq!(paths.path_to((3, 0)).unwrap().len(), 8);
+        assert_eq!(paths.dist[(0, 0)], Some(0));
+
+        let walls = dijkstra(&grid, (0, 0), &NEIGHBORS4, |&c| (c == 1).then_some(1));
+        let unit = bfs(&grid, (0, 0), &NEIGHBORS4, |&c| c == 1);
+        assert_eq!(walls.dist, unit.dist);
+    }
+
+    #[test]
+    fn test_flood_fill() {
+        let grid = Grid::parse(MAZE, |b| b);
+        let filled = flood_fill(&grid, (3, 0), &NEIGHBORS4, |&c| c == b'.');
+
+        assert!(filled[(3, 0)] && filled[(3, 1)] && filled[(0, 2)]);
+        // Only `.` is passable, so neither `S` nor `E` are filled.
+        assert!(!filled[(3, 3)] && filled[(0, 1)] && !filled[(0, 0)]);
+    }
+
+    #[test]
+    fn test_components() {
+        let grid = Grid::parse("#..#\n.#..\n#..#\n", |b| b);
+
+        let four = label_components(&grid, &NEIGHBORS4, |a, b| a == b);
+        assert_eq!(four.count(), 7);
+        assert_eq!(four.sizes.iter().sum::<usize>(), 12);
+        assert_eq!(four.labels[(1, 0)], four.labels[(2, 2)]);
+
+        let eight = label_components(&grid, &NEIGHBORS8, |a, b| a == b);
+        assert_eq!(eight.count(), 4);
+        assert_eq!(eight.labels[(0, 0)], eight.labels[(0, 2)]);
+        assert_ne!(eight.labels[(0, 0)], eight.labels[(3, 0)]);
+    }
+}
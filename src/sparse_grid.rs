@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+
+use crate::grid::Grid;
+
+/// Inclusive bounding box of signed positions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Bounds {
+    pub min: (isize, isize),
+    pub max: (isize, isize),
+}
+
+impl Bounds {
+    pub fn width(&self) -> usize {
+        self.min.0.abs_diff(self.max.0) + 1
+    }
+
+    pub fn height(&self) -> usize {
+        self.min.1.abs_diff(self.max.1) + 1
+    }
+
+    pub fn contains(&self, (x, y): (isize, isize)) -> bool {
+        self.min.0 <= x && x <= self.max.0 && self.min.1 <= y && y <= self.max.1
+    }
+
+    fn extend(&mut self, (x, y): (isize, isize)) {
+        self.min = (self.min.0.min(x), self.min.1.min(y));
+        self.max = (self.max.0.max(x), self.max.1.max(y));
+    }
+
+    fn on_border(&self, (x, y): (isize, isize)) -> bool {
+        x == self.min.0 || x == self.max.0 || y == self.min.1 || y == self.max.1
+    }
+}
+
+/// A grid that only stores its occupied cells, at any signed `(x, y)` position.
+///
+/// Meant for inputs where the space is huge or mostly empty, it uses the same conventions as
+/// [`Grid`] (`y` going down, `(0, 0)` being the first byte when parsed) and converts to and from it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SparseGrid<T> {
+    cells: HashMap<(isize, isize), T>,
+    bounds: Option<Bounds>,
+}
+
+impl<T> Default for SparseGrid<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new() -> Self {
+        Self {
+            cells: HashMap::new(),
+            bounds: None,
+        }
+    }
+
+    /// Parses text like [`Grid::parse`], only keeping the cells for which `f` returns `Some`.
+    pub fn parse(input: &str, mut f: impl FnMut(u8) -> Option<T>) -> Self {
+        let mut grid = Self::new();
+        for (y, line) in input.lines().enumerate() {
+            for (x, b) in line.bytes().enumerate() {
+                if let Some(cell) = f(b) {
+                    grid.insert((x as isize, y as isize), cell);
+                }
+            }
+        }
+        grid
+    }
+
+    /// Keeps the cells of a dense grid for which `f` returns `Some`, at the same positions.
+    pub fn from_grid<U>(grid: &Grid<U>, f: impl Fn(&U) -> Option<T>) -> Self {
+        let mut sparse = Self::new();
+        for ((x, y), cell) in grid.iter() {
+            if let Some(cell) = f(cell) {
+                sparse.insert((x as isize, y as isize), cell);
+            }
+        }
+        sparse
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// The smallest box containing every cell, `None` when empty.
+    pub fn bounds(&self) -> Option<Bounds> {
+        self.bounds
+    }
+
+    pub fn get(&self, pos: (isize, isize)) -> Option<&T> {
+        self.cells.get(&pos)
+    }
+
+    pub fn get_mut(&mut self, pos: (isize, isize)) -> Option<&mut T> {
+        self.cells.get_mut(&pos)
+    }
+
+    pub fn contains(&self, pos: (isize, isize)) -> bool {
+        self.cells.contains_key(&pos)
+    }
+
+    pub fn insert(&mut self, pos: (isize, isize), cell: T) -> Option<T> {
+        self.bounds
+            .get_or_insert(Bounds { min: pos, max: pos })
+            .extend(pos);
+        self.cells.insert(pos, cell)
+    }
+
+    /// Removes a cell, shrinking the bounding box if it was on its border.
+    ///
+    /// Shrinking has to look at every remaining cell, so this is O(n) for border cells.
+    pub fn remove(&mut self, pos: (isize, isize)) -> Option<T> {
+        let cell = self.cells.remove(&pos)?;
+
+        if self.bounds.is_some_and(|b| b.on_border(pos)) {
+            let mut positions = self.cells.keys();
+            self.bounds = positions.next().map(|&first| {
+                let mut bounds = Bounds {
+                    min: first,
+                    max: first,
+                };
+                positions.for_each(|&p| bounds.extend(p));
+                bounds
+            });
+        }
+
+        Some(cell)
+    }
+
+    /// Occupied cells in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = ((isize, isize), &T)> {
+        self.cells.iter().map(|(&pos, cell)| (pos, cell))
+    }
+
+    /// Occupied cells around `pos` for a stencil of offsets, such as `NEIGHBORS8`.
+    pub fn neighbors<'a>(
+        &'a self,
+        (x, y): (isize, isize),
+        stencil: &'a [(isize, isize)],
+    ) -> impl Iterator<Item = ((isize, isize), &'a T)> + 'a {
+        stencil.iter().filter_map(move |&(dx, dy)| {
+            let pos = (x + dx, y + dy);
+            self.get(pos).map(|cell| (pos, cell))
+        })
+    }
+
+    pub fn count_neighbors(
+        &self,
+        pos: (isize, isize),
+        stencil: &[(isize, isize)],
+        pred: impl Fn(&T) -> bool,
+    ) -> usize {
+        self.neighbors(pos, stencil)
+            .filter(|(_, cell)| pred(cell))
+            .count()
+    }
+
+    /// Dense view of the bounding box, with the position of its top left corner.
+    pub fn to_grid_with<U>(&self, f: impl Fn(Option<&T>) -> U) -> (Grid<U>, (isize, isize)) {
+        let Some(bounds) = self.bounds else {
+            return (Grid::from_vec(0, 0, Vec::new()), (0, 0));
+        };
+
+        let (x0, y0) = bounds.min;
+        let grid = Grid::from_fn(bounds.width(), bounds.height(), |x, y| {
+            f(self.get((x0 + x as isize, y0 + y as isize)))
+        });
+        (grid, bounds.min)
+    }
+
+    /// Renders the bounding box, `empty` standing for missing cells.
+    pub fn display_with(&self, empty: char, f: impl Fn(&T) -> char) -> String {
+        let (grid, _) = self.to_grid_with(|cell| cell.map_or(empty, &f));
+        grid.to_string()
+    }
+}
+
+impl<T: Clone> SparseGrid<T> {
+    /// Dense copy of the bounding box, `fill` standing for missing cells, with the position of
+    /// its top left corner.
+    pub fn to_grid(&self, fill: T) -> (Grid<T>, (isize, isize)) {
+        self.to_grid_with(|cell| cell.cloned().unwrap_or_else(|| fill.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::NEIGHBORS8;
+
+    #[test]
+    fn test_parse_and_bounds() {
+        let grid = SparseGrid::parse("...\n.#.\n..#\n", |b| (b == b'#').then_some(()));
+
+        assert_eq!(grid.len(), 2);
+        assert!(grid.contains((1, 1)));
+        assert_eq!(
+            grid.bounds(),
+            Some(Bounds {
+                min: (1, 1),
+                max: (2, 2)
+            })
+        );
+        assert_eq!(grid.count_neighbors((1, 1), &NEIGHBORS8, |_| true), 1);
+        assert_eq!(grid.count_neighbors((2, 1), &NEIGHBORS8, |_| true), 2);
+    }
+
+    #[test]
+    fn test_bounds_follow_changes() {
+        let mut grid = SparseGrid::new();
+        grid.insert((-5, 3), 'a');
+        grid.insert((2, -1), 'b');
+        grid.insert((0, 0), 'c');
+        assert_eq!(
+            grid.bounds(),
+            Some(Bounds {
+                min: (-5, -1),
+                max: (2, 3)
+            })
+        );
+
+        assert_eq!(grid.remove((-5, 3)), Some('a'));
+        assert_eq!(
+            grid.bounds(),
+            Some(Bounds {
+                min: (0, -1),
+                max: (2, 0)
+            })
+        );
+
+        grid.remove((2, -1));
+        grid.remove((0, 0));
+        assert_eq!(grid.bounds(), None);
+        assert_eq!(grid.remove((0, 0)), None);
+    }
+
+    #[test]
+    fn test_grid_round_trip() {
+        let dense = Grid::parse("#..\n.#.\n", |b| b);
+        let sparse = SparseGrid::from_grid(&dense, |&b| (b == b'#').then_some(b));
+
+        let (back, origin) = sparse.to_grid(b'.');
+        assert_eq!(origin, (0, 0));
+        assert_eq!(back, Grid::parse("#.\n.#\n", |b| b));
+
+        let mut shifted = sparse.clone();
+        shifted.insert((-1, -1), b'@');
+        assert_eq!(shifted.display_with('.', |&b| b as char), "@..\n.#.\n..#\n");
+        assert_eq!(shifted.to_grid(b'.').1, (-1, -1));
+    }
+}
@@ -0,0 +1,172 @@
+//! Draws polygons, rectangles and points as an SVG image, to look at geometry puzzles.
+//!
+//! Coordinates are tiles like in the puzzles: a vertex is the centre of its tile and a
+//! [`Rect`] covers its tiles entirely. The view box fits whatever was drawn, and strokes and
+//! labels are sized relative to it so that huge coordinates still give a readable picture.
+
+use std::fmt::Write as _;
+use std::io::{self, Write};
+
+use crate::polygon::{Rect, Vertex};
+
+enum Element {
+    Polygon(Vec<Vertex>),
+    Rect(Rect),
+    Point(Vertex),
+    Label(Vertex, String),
+}
+
+/// Fill and stroke colours of an element, any CSS colour, e.g. `"none"` or `"#ff000080"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Style<'a> {
+    pub fill: &'a str,
+    pub stroke: &'a str,
+}
+
+impl<'a> Style<'a> {
+    pub fn new(fill: &'a str, stroke: &'a str) -> Self {
+        Self { fill, stroke }
+    }
+}
+
+/// An SVG drawing, elements being painted in the order they are added.
+#[derive(Default)]
+pub struct Svg {
+    elements: Vec<(Element, String, String)>,
+    min: Option<Vertex>,
+    max: Option<Vertex>,
+}
+
+impl Svg {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn push(&mut self, element: Element, style: Style, corners: &[Vertex]) {
+        for p in corners {
+            let min = self.min.get_or_insert(*p);
+            *min = [min[0].min(p[0]), min[1].min(p[1])];
+            let max = self.max.get_or_insert(*p);
+            *max = [max[0].max(p[0]), max[1].max(p[1])];
+        }
+        self.elements
+            .push((element, style.fill.to_string(), style.stroke.to_string()));
+    }
+
+    /// A closed loop through the tile centres of `vertices`.
+    pub fn polygon(&mut self, vertices: &[Vertex], style: Style) {
+        self.push(Element::Polygon(vertices.to_vec()), style, vertices);
+    }
+
+    pub fn rect(&mut self, rect: Rect, style: Style) {
+        self.push(Element::Rect(rect), style, &[rect.min, rect.max]);
+    }
+
+    /// A dot on the tile `p`, the stroke being unused.
+    pub fn point(&mut self, p: Vertex, style: Style) {
+        self.push(Element::Point(p), style, &[p]);
+    }
+
+    /// Text centred on the tile `p`, the stroke being unused.
+    pub fn label(&mut self, p: Vertex, text: &str, style: Style) {
+        self.push(Element::Label(p, escape(text)), style, &[p]);
+    }
+
+    pub fn write(&self, w: &mut impl Write) -> io::Result<()> {
+        let (min, max) = (self.min.unwrap_or([0, 0]), self.max.unwrap_or([0, 0]));
+
+        // Tiles stick out half a tile past the extreme vertices, plus a margin around it all.
+        let size = (max[0] - min[0]).max(max[1] - min[1]) + 1;
+        let unit = size as f64 / 200.0;
+        let margin = 0.5 + 4.0 * unit;
+        let (x0, y0) = (min[0] as f64 - margin, min[1] as f64 - margin);
+        let (w_box, h_box) = (
+            (max[0] - min[0]) as f64 + 2.0 * margin,
+            (max[1] - min[1]) as f64 + 2.0 * margin,
+        );
+
+        writeln!(
+            w,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{x0} {y0} {w_box} {h_box}">"#
+        )?;
+
+        for (element, fill, stroke) in &self.elements {
+            let paint = format!(r#"fill="{fill}" stroke="{stroke}" stroke-width="{unit}""#);
+            match element {
+                Element::Polygon(vertices) => {
+                    let mut points = String::new();
+                    for v in vertices {
+                        write!(points, "{},{} ", v[0], v[1]).unwrap();
+                    }
+                    writeln!(w, r#"  <polygon points="{}" {paint}/>"#, points.trim_end())?;
+                }
+                Element::Rect(rect) => {
+                    writeln!(
+                        w,
+                        r#"  <rect x="{}" y="{}" width="{}" height="{}" {paint}/>"#,
+                        rect.min[0] as f64 - 0.5,
+                        rect.min[1] as f64 - 0.5,
+                        rect.max[0] - rect.min[0] + 1,
+                        rect.max[1] - rect.min[1] + 1,
+                    )?;
+                }
+                Element::Point(p) => {
+                    let r = (2.0 * unit).max(0.5);
+                    writeln!(
+                        w,
+                        r#"  <circle cx="{}" cy="{}" r="{r}" fill="{fill}"/>"#,
+                        p[0], p[1]
+                    )?;
+                }
+                Element::Label(p, text) => {
+                    writeln!(
+                        w,
+                        r#"  <text x="{}" y="{}" font-size="{}" text-anchor="middle" dominant-baseline="middle" fill="{fill}">{text}</text>"#,
+                        p[0],
+                        p[1],
+                        (8.0 * unit).max(1.0)
+                    )?;
+                }
+            }
+        }
+
+        writeln!(w, "</svg>")
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_svg() {
+        let mut svg = Svg::new();
+        svg.polygon(
+            &[[0, 0], [4, 0], [4, 2], [0, 2]],
+            Style::new("none", "black"),
+        );
+        svg.rect(
+            Rect::from_corners([1, 1], [2, 2]),
+            Style::new("red", "none"),
+        );
+        svg.point([4, 2], Style::new("blue", "none"));
+        svg.label([2, 1], "a < b & c", Style::new("black", "none"));
+
+        let mut out = Vec::new();
+        svg.write(&mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        assert!(out.starts_with("<svg "));
+        assert!(out.ends_with("</svg>\n"));
+        assert!(out.contains(r#"points="0,0 4,0 4,2 0,2""#));
+        assert!(out.contains(r#"<rect x="0.5" y="0.5" width="2" height="2""#));
+        assert!(out.contains(r#"<circle cx="4" cy="2""#));
+        assert!(out.contains(">a &lt; b &amp; c</text>"));
+    }
+}